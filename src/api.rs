@@ -4,20 +4,32 @@
 //! This module contains all the things needed to talk with Riot API.
 //! The most important type here is
 //! [`LeagueClient`], as it is the main way of getting the data from API. See [`LeagueClient`] for more information.
+use crate::cache::{cache_key, Cache, CacheStats, Cached, ResponseCache};
 use crate::constants::{LanguageCode, RankedQueue, RankedTier, Region};
-use crate::ddragon::DDragonClient;
-use crate::dto::api::{ChampionInfo, ChampionMastery, LeagueInfo, Summoner};
+use crate::ddragon::{DDragonClient, DDRAGON_HOST};
+use crate::dto::api::{
+    Account, ChampionInfo, ChampionMastery, ChampionRotation, ClashPlayer, ClashTeam,
+    ClashTournament, CurrentGameInfo, EnrichedMatch, EnrichedParticipant, FeaturedGames,
+    LeagueInfo, LeagueList, LiveGameParticipant, MasterySummary, Match, MatchTimeline,
+    NamedMastery, PlayerInfo, Summoner,
+};
+use crate::dto::ddragon::ChampionFullData;
+use crate::dto::tft::TftLeagueEntry;
 use crate::error::*;
-use crate::types::{Cache, Client};
-use crate::utils::{construct_hyper_client, CachedClient};
+use crate::rate_limit::{method_key, RateLimit, RateLimiter};
+use crate::types::Client;
+use crate::utils::{construct_hyper_client, encode_path_segment, parse_url, CachedClient};
+use futures::future::{self, Either};
 use futures::prelude::*;
 
 use hyper::{Body, HeaderMap, Request, Uri};
-use snafu::{ensure, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 
-use log::{debug, trace};
+use log::{debug, trace, warn};
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::env;
 
 use crate::constants::division::Division;
@@ -25,13 +37,17 @@ use serde::de::DeserializeOwned;
 use std::fmt::Debug;
 use std::str;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
 
 use async_trait::async_trait;
+use futures_timer::Delay;
 use hyper::header::HeaderValue;
 use parking_lot::Mutex;
 
 /// Main type for calling League API Endpoints.
-/// Instances of `LeagueClient` can be created using [`new`] with a [`Region`] parameter
+/// Instances of `LeagueClient` can be created using [`new`] with a [`Region`] parameter,
+/// or with [`LeagueClientBuilder`] if api key should not be read from `RIOT_API_KEY` environment variable.
 ///
 /// `LeagueClient` can have an embedded [`DDragonClient`] instance embedded in itself,
 /// reference to which can be obtained using [`ddragon`]. ***NOTE***: this method will panic if
@@ -39,6 +55,7 @@ use parking_lot::Mutex;
 ///
 /// [`new`]: #method.new
 /// [`Region`]: ../constants/region/struct.Region.html
+/// [`LeagueClientBuilder`]: struct.LeagueClientBuilder.html
 /// [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
 /// [`ddragon`]: #method.ddragon
 /// [`with_ddragon`]: #method.with_ddragon
@@ -47,9 +64,176 @@ pub struct LeagueClient {
     client: Client,
     cache: Cache,
     region: Region,
+    host: Option<String>,
     base_url: String,
+    platform_url: String,
+    regional_url: String,
+    /// Regional url of account-v1, differs from `regional_url` for OCE
+    account_url: String,
     ddragon: Option<DDragonClient>,
     api_key: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    /// Bounds number of requests in flight, see [`LeagueClientBuilder::max_concurrency`]
+    in_flight: Option<Semaphore>,
+    caching: bool,
+    cache_ttl: Option<Duration>,
+    endpoint_cache_ttls: Vec<(String, Duration)>,
+    timeout: Option<Duration>,
+    default_headers: HeaderMap,
+    last_rate_limit: Mutex<Option<RateLimit>>,
+    /// `None` unless enabled with [`LeagueClientBuilder::capture_response_headers`]
+    last_response_headers: Option<Mutex<Option<HeaderMap>>>,
+}
+
+/// Policy of retrying requests that failed with 429, 500, 502, 503, 504 or timed out.
+///
+/// Default policy does not retry at all.
+/// Delay before each retry is `base_backoff` doubled for every previous attempt,
+/// unless Riot asked to wait for specific time with `Retry-After` header.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of retries of one request
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub base_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates policy with given number of retries and delay before the first one
+    pub fn new(max_retries: u32, base_backoff: Duration) -> Self {
+        RetryPolicy {
+            max_retries,
+            base_backoff,
+        }
+    }
+
+    /// Returns delay before next retry, or `None` if request should not be retried.
+    fn backoff(&self, attempt: u32, err: &ClientError) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        match err {
+            ClientError::RateLimitExceeded {
+                retry_after: Some(after),
+                ..
+            } => Some(*after),
+            ClientError::RateLimitExceeded { .. }
+            | ClientError::InternalServerError { .. }
+            | ClientError::BadGateway { .. }
+            | ClientError::ServiceUnavailable { .. }
+            | ClientError::GatewayTimeout { .. }
+            | ClientError::Timeout { .. } => {
+                Some(self.base_backoff * 2_u32.saturating_pow(attempt))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(0, Duration::from_millis(500))
+    }
+}
+
+/// Number of match ids requested per page by [`LeagueClient::match_ids_stream`],
+/// the maximum allowed by Riot.
+///
+/// [`LeagueClient::match_ids_stream`]: struct.LeagueClient.html#method.match_ids_stream
+pub const MATCH_IDS_PAGE: i32 = 100;
+
+/// Filters for [`get_match_ids_with_query`], unset fields are left to Riot's defaults.
+///
+/// ```no_run
+/// use narwhalol::MatchIdQuery;
+/// // Ranked solo/duo games since 2021-06-16
+/// let query = MatchIdQuery::new()
+///     .queue(420)
+///     .start_time(1_623_801_600)
+///     .count(100);
+/// ```
+///
+/// [`get_match_ids_with_query`]: struct.LeagueClient.html#method.get_match_ids_with_query
+#[derive(Debug, Clone, Default)]
+pub struct MatchIdQuery {
+    queue: Option<i32>,
+    match_type: Option<String>,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    start: Option<i32>,
+    count: Option<i32>,
+}
+
+impl MatchIdQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches of given queue id, e.g. 420 for ranked solo/duo
+    pub fn queue(mut self, queue: i32) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Only matches of given type: `ranked`, `normal`, `tourney` or `tutorial`
+    pub fn match_type(mut self, match_type: &str) -> Self {
+        self.match_type = Some(match_type.to_owned());
+        self
+    }
+
+    /// Only matches played after given epoch timestamp in seconds
+    pub fn start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Only matches played before given epoch timestamp in seconds
+    pub fn end_time(mut self, end_time: i64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+
+    /// Index of the first match id to return, Riot defaults to 0
+    pub fn start(mut self, start: i32) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Number of match ids to return, Riot defaults to 20 and allows up to 100
+    pub fn count(mut self, count: i32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    fn query_string(&self) -> String {
+        let params = [
+            ("queue", self.queue.map(|v| v.to_string())),
+            ("type", self.match_type.clone()),
+            ("startTime", self.start_time.map(|v| v.to_string())),
+            ("endTime", self.end_time.map(|v| v.to_string())),
+            ("start", self.start.map(|v| v.to_string())),
+            ("count", self.count.map(|v| v.to_string())),
+        ];
+        params
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| format!("{}={}", name, v)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+/// Order of masteries returned by [`get_champion_masteries_sorted`], always descending.
+///
+/// [`get_champion_masteries_sorted`]: struct.LeagueClient.html#method.get_champion_masteries_sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MasterySort {
+    /// Most champion points first
+    Points,
+    /// Highest mastery level first, ties broken by champion points
+    Level,
+    /// Most recently played first
+    LastPlayTime,
 }
 
 impl LeagueClient {
@@ -58,27 +242,49 @@ impl LeagueClient {
     /// # Panics
     /// This will panic if you do not provide the RIOT_API_KEY environment variable with value being api token.
     pub fn new(region: Region) -> Result<LeagueClient, ClientError> {
-        let base_url = format!("https://{}.api.riotgames.com/lol", region.as_platform_str());
-        let api_key = std::env::var("RIOT_API_KEY").context(NoToken {})?;
-        check_token(&api_key)?;
-        let client = construct_hyper_client();
-        let cache: Cache = Arc::new(Mutex::new(HashMap::new()));
+        LeagueClientBuilder::new(region).build()
+    }
+
+    /// Creates a [`LeagueClientBuilder`] for the region.
+    ///
+    /// [`LeagueClientBuilder`]: struct.LeagueClientBuilder.html
+    pub fn builder(region: Region) -> LeagueClientBuilder {
+        LeagueClientBuilder::new(region)
+    }
+
+    /// Adds an embedded ddragon client instance to league api client that shares cache and client with parent.
+    ///
+    /// Fails if the latest DDragon version could not be fetched,
+    /// e.g. with [`NoDDragonVersions`] if DDragon returned an empty version list.
+    ///
+    /// [`NoDDragonVersions`]: ../error/enum.ClientError.html#variant.NoDDragonVersions
+    pub async fn with_ddragon(self, language: LanguageCode) -> Result<Self, ClientError> {
+        let ddragon = DDragonClient::new_for_lapi(
+            self.client.clone(),
+            self.cache.clone(),
+            language,
+            DDRAGON_HOST,
+        )
+        .await?;
         Ok(LeagueClient {
-            region,
-            base_url,
-            ddragon: None,
-            cache,
-            client,
-            api_key,
+            ddragon: Some(ddragon),
+            ..self
         })
     }
 
-    /// Adds an embedded ddragon client instance to league api client that shares cache and client with parent.
-    pub async fn with_ddragon(self, language: LanguageCode) -> Self {
-        let ddragon =
-            DDragonClient::new_for_lapi(self.client.clone(), self.cache.clone(), language)
-                .await
-                .unwrap();
+    /// Same as [`with_ddragon`], but pins embedded ddragon client to the given version
+    /// (e.g. `10.10.3216176`) instead of the latest one, e.g. to interpret an old match
+    /// with data of its patch. No request is made, as the version is already known.
+    ///
+    /// [`with_ddragon`]: #method.with_ddragon
+    pub fn with_ddragon_version(self, language: LanguageCode, version: &str) -> Self {
+        let ddragon = DDragonClient::pinned_for_lapi(
+            self.client.clone(),
+            self.cache.clone(),
+            language,
+            DDRAGON_HOST,
+            version,
+        );
         LeagueClient {
             ddragon: Some(ddragon),
             ..self
@@ -92,8 +298,8 @@ impl LeagueClient {
     ///
     /// [`with_ddragon`]: #method.with_ddragon
     pub fn ddragon(&mut self) -> &mut DDragonClient {
-        match self.ddragon {
-            Some(ref mut dd) => dd,
+        match self.try_ddragon() {
+            Some(dd) => dd,
             None => panic!(
                 "You are trying to access ddragon client without creating an embedded one in LeagueClient!\n\
                 See with_ddragon method in League Client.\
@@ -101,9 +307,80 @@ impl LeagueClient {
         }
     }
 
+    /// Non-panicking version of [`ddragon`], returns `None` if [`with_ddragon`] was not called.
+    ///
+    /// [`ddragon`]: #method.ddragon
+    /// [`with_ddragon`]: #method.with_ddragon
+    pub fn try_ddragon(&mut self) -> Option<&mut DDragonClient> {
+        self.ddragon.as_mut()
+    }
+
+    /// Region the client sends requests to.
+    pub fn region(&self) -> Region {
+        self.region.clone()
+    }
+
+    /// Switches the client to another region, keeping cache and all other settings.
+    ///
+    /// Cache is keyed by full url, so responses of the previous region are never served
+    /// for the new one. A custom [`host`] set on the builder stays in use.
+    ///
+    /// [`host`]: struct.LeagueClientBuilder.html#method.host
+    pub fn set_region(&mut self, region: Region) {
+        let (platform_url, regional_url, account_url) = api_urls(&region, self.host.as_deref());
+        self.base_url = format!("{}/lol", platform_url);
+        self.platform_url = platform_url;
+        self.regional_url = regional_url;
+        self.account_url = account_url;
+        self.region = region;
+    }
+
+    /// Base url of League API requests, e.g. `https://na1.api.riotgames.com/lol`.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Rate limit budget reported by Riot with the most recent response, `None` before the first request.
+    pub fn last_rate_limit(&self) -> Option<RateLimit> {
+        self.last_rate_limit.lock().clone()
+    }
+
+    /// Headers of the most recent League API response, including error ones.
+    ///
+    /// Always `None` unless enabled with [`LeagueClientBuilder::capture_response_headers`].
+    ///
+    /// [`LeagueClientBuilder::capture_response_headers`]: struct.LeagueClientBuilder.html#method.capture_response_headers
+    pub fn last_response_headers(&self) -> Option<HeaderMap> {
+        self.last_response_headers.as_ref()?.lock().clone()
+    }
+
+    /// Removes all responses from cache, including the ones of embedded [`DDragonClient`].
+    ///
+    /// [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
+    pub async fn clear_cache(&self) {
+        self.cache.clear().await
+    }
+
+    /// Cache hit and miss counters along with current number of cached responses
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Replaces api key used by all following requests, keeping the cache.
+    ///
+    /// The key is validated the same way as in [`LeagueClientBuilder::api_key`],
+    /// current key is kept if the new one is rejected.
+    ///
+    /// [`LeagueClientBuilder::api_key`]: struct.LeagueClientBuilder.html#method.api_key
+    pub fn set_api_key(&mut self, api_key: &str) -> Result<(), ClientError> {
+        check_token(api_key)?;
+        self.api_key = api_key.to_owned();
+        Ok(())
+    }
+
     ///Get summoner by plaintext name
     /// # Example
-    /// ```
+    /// ```no_run
     /// use narwhalol::{LeagueClient, Region, dto::api::Summoner, error::ClientError};
     ///
     /// fn main() -> Result<(), ClientError> {
@@ -118,258 +395,2284 @@ impl LeagueClient {
     /// ```
     ///
     pub async fn get_summoner_by_name(&self, name: &str) -> Result<Summoner, ClientError> {
-        println!("Getting summoner with name: {}", &name);
-        let url: Uri = format!("{}/summoner/v4/summoners/by-name/{}", self.base_url, name)
-            .parse()
-            .unwrap();
+        debug!("Getting summoner with name: {}", &name);
+        let url: Uri = parse_url(&format!(
+            "{}/summoner/v4/summoners/by-name/{}",
+            self.base_url,
+            encode_path_segment(name)
+        ))?;
         debug!("Constructed url: {:?}", &url);
         self.cached_resp(url).await
     }
 
-    pub async fn get_champion_info(&mut self) -> Result<ChampionInfo, ClientError> {
-        let url: Uri = format!("{}/platform/v3/champion-rotations", self.base_url)
-            .parse()
-            .unwrap();
+    /// Same as [`get_summoner_by_name`], but returns `None` instead of [`DataNotFound`]
+    /// if there is no summoner with such name.
+    ///
+    /// [`get_summoner_by_name`]: #method.get_summoner_by_name
+    /// [`DataNotFound`]: ../error/enum.ClientError.html#variant.DataNotFound
+    pub async fn find_summoner_by_name(&self, name: &str) -> Result<Option<Summoner>, ClientError> {
+        match self.get_summoner_by_name(name).await {
+            Ok(summoner) => Ok(Some(summoner)),
+            Err(ClientError::DataNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get summoners by plaintext names concurrently, e.g. all players of a lobby.
+    ///
+    /// Results are returned in order of `names`, so one unknown name does not fail the whole batch.
+    /// Requests still go through configured [`RateLimiter`].
+    ///
+    /// [`RateLimiter`]: ../rate_limit/struct.RateLimiter.html
+    pub async fn get_summoners_by_names(
+        &self,
+        names: &[&str],
+    ) -> Vec<Result<Summoner, ClientError>> {
+        future::join_all(names.iter().map(|name| self.get_summoner_by_name(name))).await
+    }
+
+    /// Get summoner by PUUID
+    pub async fn get_summoner_by_puuid(&self, puuid: &str) -> Result<Summoner, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/summoner/v4/summoners/by-puuid/{}",
+            self.base_url, puuid
+        ))?;
+        debug!("Constructed url: {:?}", &url);
         self.cached_resp(url).await
     }
 
-    pub async fn get_champion_masteries(
+    pub async fn get_champion_info(&self) -> Result<ChampionInfo, ClientError> {
+        let url: Uri = parse_url(&format!("{}/platform/v3/champion-rotations", self.base_url))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get full DDragon data of champions in current free rotation.
+    ///
+    /// Requires DDragon client embedded with [`with_ddragon`],
+    /// returns [`DDragonNotAttached`] otherwise.
+    ///
+    /// [`with_ddragon`]: #method.with_ddragon
+    /// [`DDragonNotAttached`]: ../error/enum.ClientError.html#variant.DDragonNotAttached
+    pub async fn get_free_rotation_champions(
+        &mut self,
+    ) -> Result<Vec<ChampionFullData>, ClientError> {
+        let rotation = self.get_champion_info().await?;
+        self.champions_by_keys(&rotation.free_champion_ids).await
+    }
+
+    /// Get free champion rotation for everyone and for new players, with full DDragon data.
+    ///
+    /// Requires DDragon client embedded with [`with_ddragon`],
+    /// returns [`DDragonNotAttached`] otherwise.
+    ///
+    /// [`with_ddragon`]: #method.with_ddragon
+    /// [`DDragonNotAttached`]: ../error/enum.ClientError.html#variant.DDragonNotAttached
+    pub async fn get_champion_rotation(&mut self) -> Result<ChampionRotation, ClientError> {
+        let rotation = self.get_champion_info().await?;
+        Ok(ChampionRotation {
+            regular: self.champions_by_keys(&rotation.free_champion_ids).await?,
+            beginner: self
+                .champions_by_keys(&rotation.free_champion_ids_for_new_players)
+                .await?,
+            beginner_max_level: u8::try_from(rotation.max_new_player_level).unwrap_or(u8::MAX),
+        })
+    }
+
+    async fn champions_by_keys(
+        &mut self,
+        keys: &[i64],
+    ) -> Result<Vec<ChampionFullData>, ClientError> {
+        let ddragon = self.try_ddragon().context(DDragonNotAttached)?;
+        let mut result = Vec::with_capacity(keys.len());
+        for &key in keys {
+            result.push(ddragon.get_champion_by_key(key as u64).await?);
+        }
+        Ok(result)
+    }
+
+    /// Attach champion names and square icons to masteries.
+    ///
+    /// Requires DDragon client embedded with [`with_ddragon`],
+    /// returns [`DDragonNotAttached`] otherwise.
+    ///
+    /// [`with_ddragon`]: #method.with_ddragon
+    /// [`DDragonNotAttached`]: ../error/enum.ClientError.html#variant.DDragonNotAttached
+    pub async fn enrich_masteries(
         &mut self,
+        masteries: Vec<ChampionMastery>,
+    ) -> Result<Vec<NamedMastery>, ClientError> {
+        let ddragon = self.try_ddragon().context(DDragonNotAttached)?;
+        let champions = ddragon.get_champions().await?;
+        masteries
+            .into_iter()
+            .map(|mastery| {
+                let champion =
+                    champions
+                        .by_key(mastery.champion_id as u64)
+                        .context(ChampionNotFound {
+                            name: mastery.champion_id.to_string(),
+                        })?;
+                Ok(NamedMastery {
+                    champion_name: champion.name.clone(),
+                    square_icon_url: ddragon.champion_square_url(&champion.id),
+                    mastery,
+                })
+            })
+            .collect::<Result<Vec<_>, ClientError>>()
+    }
+
+    pub async fn get_champion_masteries(
+        &self,
         summoner_id: &str,
     ) -> Result<Vec<ChampionMastery>, ClientError> {
         trace!("Getting champion masteries for id: {}", &summoner_id);
-        let url: Uri = format!(
+        let url: Uri = parse_url(&format!(
             "{}/champion-mastery/v4/champion-masteries/by-summoner/{}",
             self.base_url, summoner_id
-        )
-        .parse()
-        .unwrap();
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get number of champions at every mastery level, e.g. how many champions are at level 7.
+    pub async fn get_mastery_level_counts(
+        &self,
+        summoner_id: &str,
+    ) -> Result<HashMap<i32, usize>, ClientError> {
+        let masteries = self.get_champion_masteries(summoner_id).await?;
+        let mut counts = HashMap::new();
+        for mastery in masteries {
+            *counts.entry(mastery.champion_level).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
+    /// Get top `count` champion masteries of a summoner, sorted by champion points
+    pub async fn get_top_champion_masteries(
+        &self,
+        summoner_id: &str,
+        count: i32,
+    ) -> Result<Vec<ChampionMastery>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/champion-mastery/v4/champion-masteries/by-summoner/{}/top?count={}",
+            self.base_url, summoner_id, count
+        ))?;
         self.cached_resp(url).await
     }
 
     pub async fn get_champion_mastery_by_id(
-        &mut self,
+        &self,
         summoner_id: &str,
         champion_id: u64,
     ) -> Result<ChampionMastery, ClientError> {
-        let url: Uri = format!(
+        let url: Uri = parse_url(&format!(
             "{}/champion-mastery/v4/champion-masteries/by-summoner/{}/by-champion/{}",
             self.base_url, summoner_id, champion_id
-        )
-        .parse()
-        .unwrap();
+        ))?;
         self.cached_resp(url).await
     }
 
-    pub async fn get_total_mastery_score(&mut self, summoner_id: &str) -> Result<i32, ClientError> {
-        let url: Uri = format!(
+    pub async fn get_total_mastery_score(&self, summoner_id: &str) -> Result<i32, ClientError> {
+        let url: Uri = parse_url(&format!(
             "{}/champion-mastery/v4/scores/by-summoner/{}",
             self.base_url, summoner_id
-        )
-        .parse()
-        .unwrap();
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Same as [`get_champion_masteries`], but sorted by `by` and,
+    /// if `min_level` is given, without champions of lower mastery level.
+    ///
+    /// [`get_champion_masteries`]: #method.get_champion_masteries
+    pub async fn get_champion_masteries_sorted(
+        &self,
+        summoner_id: &str,
+        by: MasterySort,
+        min_level: Option<i32>,
+    ) -> Result<Vec<ChampionMastery>, ClientError> {
+        let mut masteries = self.get_champion_masteries(summoner_id).await?;
+        if let Some(min_level) = min_level {
+            masteries.retain(|m| m.champion_level >= min_level);
+        }
+        match by {
+            MasterySort::Points => masteries.sort_by_key(|m| Reverse(m.champion_points)),
+            MasterySort::Level => {
+                masteries.sort_by_key(|m| Reverse((m.champion_level, m.champion_points)))
+            }
+            MasterySort::LastPlayTime => masteries.sort_by_key(|m| Reverse(m.last_play_time)),
+        }
+        Ok(masteries)
+    }
+
+    /// Get total mastery score together with number of played champions and top 3 of them.
+    ///
+    /// Score and masteries are requested concurrently.
+    pub async fn get_mastery_summary(
+        &self,
+        summoner_id: &str,
+    ) -> Result<MasterySummary, ClientError> {
+        let (total_score, masteries) = futures::join!(
+            self.get_total_mastery_score(summoner_id),
+            self.get_champion_masteries(summoner_id)
+        );
+        let mut masteries = masteries?;
+        let champion_count = masteries.len();
+        // Riot returns masteries sorted by champion points, but do not rely on it
+        masteries.sort_by_key(|m| Reverse(m.champion_points));
+        masteries.truncate(3);
+        Ok(MasterySummary {
+            total_score: total_score?,
+            champion_count,
+            top_champions: masteries,
+        })
+    }
+
+    /// Get one page of league entries of given queue, tier and division.
+    ///
+    /// Unlike [`get_league_exp_entries`], this only covers IRON through DIAMOND,
+    /// apex tiers are available through [`get_league_exp_entries`] or the apex league endpoints.
+    /// `page` starts from 1, which is also the default.
+    ///
+    /// [`get_league_exp_entries`]: #method.get_league_exp_entries
+    pub async fn get_league_entries(
+        &self,
+        queue: RankedQueue,
+        tier: RankedTier,
+        division: Division,
+        page: Option<i32>,
+    ) -> Result<Vec<LeagueInfo>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/league/v4/entries/{}/{}/{}?page={}",
+            self.base_url,
+            queue,
+            tier,
+            division,
+            page.unwrap_or(1)
+        ))?;
         self.cached_resp(url).await
     }
 
     pub async fn get_league_exp_entries(
-        &mut self,
+        &self,
         queue: RankedQueue,
         tier: RankedTier,
         division: Division,
         pages: Option<i32>,
     ) -> Result<Vec<LeagueInfo>, ClientError> {
         let url: Uri = match pages {
-            Some(p) => format!(
+            Some(p) => parse_url(&format!(
                 "{}/league-exp/v4/entries/{}/{}/{}?page={}",
                 &self.base_url, queue, tier, division, p
-            )
-            .parse()
-            .unwrap(),
-            None => format!(
+            ))?,
+            None => parse_url(&format!(
                 "{}/league-exp/v4/entries/{}/{}/{}",
                 &self.base_url, queue, tier, division
-            )
-            .parse()
-            .unwrap(),
+            ))?,
         };
 
         self.cached_resp(url).await
     }
 
-    #[cfg(test)]
-    pub(crate) fn get_status(&self, status: u16) -> Result<(), ClientError> {
-        ClientError::check_status(self.region.clone(), status)
+    /// Lazily walks all pages of [`get_league_exp_entries`] until an empty page is returned,
+    /// yielding entries one by one. Stream ends after the first error.
+    ///
+    /// [`get_league_exp_entries`]: #method.get_league_exp_entries
+    pub fn league_exp_stream(
+        &self,
+        queue: RankedQueue,
+        tier: RankedTier,
+        division: Division,
+    ) -> impl Stream<Item = Result<LeagueInfo, ClientError>> + '_ {
+        let pages = stream::unfold(Some((self, 1)), move |state| {
+            let (queue, tier, division) = (queue.clone(), tier.clone(), division.clone());
+            async move {
+                let (lapi, page) = state?;
+                match lapi
+                    .get_league_exp_entries(queue, tier, division, Some(page))
+                    .await
+                {
+                    Ok(entries) if entries.is_empty() => None,
+                    Ok(entries) => Some((Ok(entries), Some((lapi, page + 1)))),
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        });
+        pages.flat_map(|page| match page {
+            Ok(entries) => stream::iter(entries.into_iter().map(Ok)).left_stream(),
+            Err(e) => stream::once(future::ready(Err(e))).right_stream(),
+        })
     }
-}
 
-#[async_trait]
-impl CachedClient for LeagueClient {
-    async fn cached_resp<T: Debug + DeserializeOwned + Send>(
+    /// Get ranked entries of a summoner in all queues
+    pub async fn get_league_entries_by_summoner(
         &self,
-        url: Uri,
-    ) -> Result<T, ClientError> {
-        let maybe_resp: Option<T> = self
-            .cache
-            .lock()
-            .get(&url)
-            .map(|res| serde_json::from_str(res).unwrap());
-
-        if let Some(resp) = maybe_resp {
-            debug!("Found cached: {:?}", resp);
-            Ok(resp)
-        } else {
-            debug!("Nothing in cache. Fetching from league API...");
-            // We got nothing in cache, try fetching from utl
-            let url2 = url.clone();
-            let header = HeaderValue::from_str(&self.api_key).unwrap();
-            let req = Request::builder()
-                .header("X-Riot-Token", header)
-                .uri(url)
-                .body(Body::default())
-                .unwrap();
-            let resp = self.client.request(req).await.context(HyperError)?;
-            let body = resp.into_body();
-            let bytes = hyper::body::to_bytes(body).await.context(HyperError)?;
-            let string_response = String::from_utf8_lossy(&bytes);
-            debug!("Deserializing...");
-            let deserialized: T = serde_json::from_str(&string_response).unwrap();
-            self.cache.lock().insert(url2, string_response.into_owned());
-            Ok(deserialized)
-        }
+        summoner_id: &str,
+    ) -> Result<Vec<LeagueInfo>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/league/v4/entries/by-summoner/{}",
+            self.base_url, summoner_id
+        ))?;
+        self.cached_resp(url).await
     }
-}
 
-impl Default for LeagueClient {
-    fn default() -> LeagueClient {
-        LeagueClient::new(Region::default()).expect("Please provide API_KEY environment variable")
+    /// Get challenger league of the queue
+    pub async fn get_challenger_league(
+        &self,
+        queue: RankedQueue,
+    ) -> Result<LeagueList, ClientError> {
+        self.get_apex_league("challengerleagues", queue).await
     }
-}
-
-fn check_token(token: &str) -> Result<(), ClientError> {
-    ensure!(
-        token.contains("RGAPI"),
-        WrongToken {
-            token: token.to_owned()
-        }
-    );
-    ensure!(
-        token.len() == 42_usize,
-        WrongToken {
-            token: token.to_owned()
-        }
-    );
-    Ok(())
-}
 
-#[cfg(test)]
-mod tests {
-    use super::LeagueClient;
-    use crate::constants::{LanguageCode, RankedQueue, RankedTier, Region};
+    /// Get grandmaster league of the queue
+    pub async fn get_grandmaster_league(
+        &self,
+        queue: RankedQueue,
+    ) -> Result<LeagueList, ClientError> {
+        self.get_apex_league("grandmasterleagues", queue).await
+    }
 
-    use futures::prelude::*;
-    use futures::{Future, FutureExt, TryFutureExt};
-    use pretty_env_logger;
+    /// Get master league of the queue
+    pub async fn get_master_league(&self, queue: RankedQueue) -> Result<LeagueList, ClientError> {
+        self.get_apex_league("masterleagues", queue).await
+    }
 
-    use crate::constants::division::Division;
-    use crate::dto::api::{ChampionInfo, ChampionMastery, Summoner};
-    use crate::dto::ddragon::ChampionFullData;
-    use crate::error::ClientError;
-    use crate::types::Cache;
-    use log::debug;
-    use std::time::Instant;
+    /// Get league by its id, e.g. `league_id` of [`LeagueInfo`]
+    ///
+    /// [`LeagueInfo`]: ../dto/api/struct.LeagueInfo.html
+    pub async fn get_league_by_id(&self, league_id: &str) -> Result<LeagueList, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/league/v4/leagues/{}",
+            self.base_url, league_id
+        ))?;
+        self.cached_resp(url).await
+    }
 
-    #[cfg(test)]
-    fn print_cache(cache: Cache) {
-        debug!("{:?}", cache.lock().keys().collect::<Vec<_>>())
+    async fn get_apex_league(
+        &self,
+        league: &str,
+        queue: RankedQueue,
+    ) -> Result<LeagueList, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/league/v4/{}/by-queue/{}",
+            self.base_url, league, queue
+        ))?;
+        self.cached_resp(url).await
     }
 
-    #[test]
-    fn gets_summoner_data() {
-        smol::run(async {
-            pretty_env_logger::init();
-            let mut lapi = LeagueClient::new(Region::NA).unwrap();
-            let sum = lapi.get_summoner_by_name("Santorin").await.unwrap();
-            assert_eq!(
-                &sum.account_id,
-                "rPnj4h5W6OhejxB-AO3hLOQctgZcckqV_82N_8_WuCFdO2A"
-            )
-        })
+    /// Get game the summoner is currently playing.
+    ///
+    /// Returns [`DataNotFound`] if the summoner is not in game right now.
+    /// Live game data is cached only for 30 seconds by default.
+    ///
+    /// [`DataNotFound`]: ../error/enum.ClientError.html#variant.DataNotFound
+    pub async fn get_active_game(&self, summoner_id: &str) -> Result<CurrentGameInfo, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/spectator/v4/active-games/by-summoner/{}",
+            self.base_url, summoner_id
+        ))?;
+        self.cached_resp(url).await
     }
 
-    #[test]
-    fn lapi_caches_properly() {
+    /// Resolve champions and ranked entries of live game participants, e.g. for a stream overlay.
+    ///
+    /// Ranked entries of all participants are requested concurrently, and a failed request
+    /// only affects its own participant. Participants are in the same order as in `game`.
+    ///
+    /// Requires DDragon client embedded with [`with_ddragon`],
+    /// returns [`DDragonNotAttached`] otherwise.
+    ///
+    /// [`with_ddragon`]: #method.with_ddragon
+    /// [`DDragonNotAttached`]: ../error/enum.ClientError.html#variant.DDragonNotAttached
+    pub async fn enrich_live_game(
+        &mut self,
+        game: &CurrentGameInfo,
+    ) -> Result<Vec<LiveGameParticipant>, ClientError> {
+        let ddragon = self.try_ddragon().context(DDragonNotAttached)?;
+        let champions = ddragon.get_champions().await?;
+        let this = &*self;
+        let leagues = future::join_all(
+            game.participants
+                .iter()
+                .map(|p| this.get_league_entries_by_summoner(&p.summoner_id)),
+        )
+        .await;
+        Ok(game
+            .participants
+            .iter()
+            .zip(leagues)
+            .map(|(participant, leagues)| LiveGameParticipant {
+                participant: participant.clone(),
+                champion: champions.by_key(participant.champion_id as u64).cloned(),
+                leagues,
+            })
+            .collect())
+    }
+
+    /// Get list of games currently featured in the client.
+    ///
+    /// Featured games rotate every few minutes, so they are cached for 2 minutes by default.
+    pub async fn get_featured_games(&self) -> Result<FeaturedGames, ClientError> {
+        let url: Uri = parse_url(&format!("{}/spectator/v4/featured-games", self.base_url))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get match by its id (e.g. `EUW1_4677614478`).
+    ///
+    /// Matches are served from the regional host of the client's region.
+    pub async fn get_match(&self, match_id: &str) -> Result<Match, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/lol/match/v5/matches/{}",
+            self.regional_url, match_id
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get matches by ids concurrently, e.g. the ones returned by [`get_match_ids_by_puuid`].
+    ///
+    /// Results are returned in order of `match_ids`, so one failed match does not fail the whole batch.
+    /// Requests still go through configured [`RateLimiter`] and [`max_concurrency`].
+    ///
+    /// [`get_match_ids_by_puuid`]: #method.get_match_ids_by_puuid
+    /// [`RateLimiter`]: ../rate_limit/struct.RateLimiter.html
+    /// [`max_concurrency`]: struct.LeagueClientBuilder.html#method.max_concurrency
+    pub async fn get_matches(&self, match_ids: &[&str]) -> Vec<Result<Match, ClientError>> {
+        future::join_all(match_ids.iter().map(|id| self.get_match(id))).await
+    }
+
+    /// Get match with participants joined to their champions, summoner spells and items.
+    ///
+    /// Requires DDragon client embedded with [`with_ddragon`],
+    /// returns [`DDragonNotAttached`] otherwise.
+    ///
+    /// [`with_ddragon`]: #method.with_ddragon
+    /// [`DDragonNotAttached`]: ../error/enum.ClientError.html#variant.DDragonNotAttached
+    pub async fn get_match_enriched(
+        &mut self,
+        match_id: &str,
+    ) -> Result<EnrichedMatch, ClientError> {
+        let data = self.get_match(match_id).await?;
+        let ddragon = self.try_ddragon().context(DDragonNotAttached)?;
+        let champions = ddragon.get_champions().await?;
+        let spells = ddragon.get_summoner_spells().await?;
+        let items = ddragon.get_items().await?;
+        let participants =
+            data.info
+                .participants
+                .iter()
+                .map(|p| {
+                    let champion = champions.by_key(p.champion_id as u64).cloned().context(
+                        ChampionNotFound {
+                            name: p.champion_name.clone(),
+                        },
+                    )?;
+                    let summoner_spells = [p.summoner1_id, p.summoner2_id]
+                        .iter()
+                        .filter_map(|&id| spells.by_key(id as u32).cloned())
+                        .collect();
+                    let items = [
+                        p.item0, p.item1, p.item2, p.item3, p.item4, p.item5, p.item6,
+                    ]
+                    .iter()
+                    .filter(|&&id| id != 0)
+                    .filter_map(|id| items.data.get(&id.to_string()).cloned())
+                    .collect();
+                    Ok(EnrichedParticipant {
+                        participant_id: p.participant_id,
+                        champion,
+                        summoner_spells,
+                        items,
+                    })
+                })
+                .collect::<Result<Vec<_>, ClientError>>()?;
+        Ok(EnrichedMatch { data, participants })
+    }
+
+    /// Get frame-by-frame timeline of a match
+    pub async fn get_match_timeline(&self, match_id: &str) -> Result<MatchTimeline, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/lol/match/v5/matches/{}/timeline",
+            self.regional_url, match_id
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get ids of matches played by a player, latest first.
+    ///
+    /// `start` defaults to 0 and `count` defaults to 20, same as in Riot API.
+    pub async fn get_match_ids_by_puuid(
+        &self,
+        puuid: &str,
+        start: Option<i32>,
+        count: Option<i32>,
+    ) -> Result<Vec<String>, ClientError> {
+        let query = MatchIdQuery::new()
+            .start(start.unwrap_or(0))
+            .count(count.unwrap_or(20));
+        self.get_match_ids_with_query(puuid, &query).await
+    }
+
+    /// Get ids of matches played by the player filtered by queue, type and time.
+    pub async fn get_match_ids_with_query(
+        &self,
+        puuid: &str,
+        query: &MatchIdQuery,
+    ) -> Result<Vec<String>, ClientError> {
+        let mut url = format!(
+            "{}/lol/match/v5/matches/by-puuid/{}/ids",
+            self.regional_url, puuid
+        );
+        let query = query.query_string();
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+        let url: Uri = parse_url(&url)?;
+        self.cached_resp(url).await
+    }
+
+    /// Lazily walks the whole match history of the player, most recent first,
+    /// requesting [`MATCH_IDS_PAGE`] ids at a time until Riot returns fewer than requested.
+    /// Stream ends after the first error.
+    ///
+    /// [`MATCH_IDS_PAGE`]: constant.MATCH_IDS_PAGE.html
+    pub fn match_ids_stream<'a>(
+        &'a self,
+        puuid: &'a str,
+    ) -> impl Stream<Item = Result<String, ClientError>> + 'a {
+        let pages = stream::unfold(Some(0), move |start| async move {
+            let start = start?;
+            match self
+                .get_match_ids_by_puuid(puuid, Some(start), Some(MATCH_IDS_PAGE))
+                .await
+            {
+                Ok(ids) if ids.is_empty() => None,
+                Ok(ids) if ids.len() < MATCH_IDS_PAGE as usize => Some((Ok(ids), None)),
+                Ok(ids) => Some((Ok(ids), Some(start + MATCH_IDS_PAGE))),
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        pages.flat_map(|page| match page {
+            Ok(ids) => stream::iter(ids.into_iter().map(Ok)).left_stream(),
+            Err(e) => stream::once(future::ready(Err(e))).right_stream(),
+        })
+    }
+
+    /// Get Riot account by Riot ID, e.g. `get_account_by_riot_id("Hide on bush", "KR1")`
+    /// for `Hide on bush#KR1`.
+    pub async fn get_account_by_riot_id(
+        &self,
+        game_name: &str,
+        tag_line: &str,
+    ) -> Result<Account, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/riot/account/v1/accounts/by-riot-id/{}/{}",
+            self.account_url,
+            encode_path_segment(game_name),
+            encode_path_segment(tag_line)
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get Riot account by PUUID
+    pub async fn get_account_by_puuid(&self, puuid: &str) -> Result<Account, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/riot/account/v1/accounts/by-puuid/{}",
+            self.account_url, puuid
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get third party verification code set by the summoner in client settings.
+    ///
+    /// Returns `None` if the summoner has no code set.
+    pub async fn get_third_party_code(
+        &self,
+        summoner_id: &str,
+    ) -> Result<Option<String>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/platform/v4/third-party-code/by-summoner/{}",
+            self.base_url, summoner_id
+        ))?;
+        match self.cached_resp(url).await {
+            Ok(code) => Ok(Some(code)),
+            Err(ClientError::DataNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get clash registrations of the summoner.
+    ///
+    /// Returns an empty list, not an error, if the summoner is not registered for any tournament.
+    pub async fn get_clash_players_by_summoner(
+        &self,
+        summoner_id: &str,
+    ) -> Result<Vec<ClashPlayer>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/clash/v1/players/by-summoner/{}",
+            self.base_url, summoner_id
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get clash team by id
+    pub async fn get_clash_team(&self, team_id: &str) -> Result<ClashTeam, ClientError> {
+        let url: Uri = parse_url(&format!("{}/clash/v1/teams/{}", self.base_url, team_id))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get all active and upcoming clash tournaments
+    pub async fn get_clash_tournaments(&self) -> Result<Vec<ClashTournament>, ClientError> {
+        let url: Uri = parse_url(&format!("{}/clash/v1/tournaments", self.base_url))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get challenges progress of the player
+    pub async fn get_challenge_player_data(&self, puuid: &str) -> Result<PlayerInfo, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/challenges/v1/player-data/{}",
+            self.base_url, puuid
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get TFT summoner by plaintext name.
+    ///
+    /// TFT summoners have the same shape as LoL ones, but ids may differ
+    /// if TFT uses a separate api key.
+    pub async fn get_tft_summoner_by_name(&self, name: &str) -> Result<Summoner, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/tft/summoner/v1/summoners/by-name/{}",
+            self.platform_url,
+            encode_path_segment(name)
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get TFT league entries of the summoner, including Hyper Roll ones.
+    pub async fn get_tft_league_entries_by_summoner(
+        &self,
+        summoner_id: &str,
+    ) -> Result<Vec<TftLeagueEntry>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/tft/league/v1/entries/by-summoner/{}",
+            self.platform_url, summoner_id
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get ids of TFT matches played by the player, most recent first.
+    ///
+    /// Like [`get_match_ids_by_puuid`], `start` defaults to 0 and `count` to 20.
+    ///
+    /// [`get_match_ids_by_puuid`]: #method.get_match_ids_by_puuid
+    pub async fn get_tft_match_ids_by_puuid(
+        &self,
+        puuid: &str,
+        start: Option<i32>,
+        count: Option<i32>,
+    ) -> Result<Vec<String>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/tft/match/v1/matches/by-puuid/{}/ids?start={}&count={}",
+            self.regional_url,
+            puuid,
+            start.unwrap_or(0),
+            count.unwrap_or(20)
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get raw json of any League API endpoint, including ones without typed method.
+    ///
+    /// `path` is appended to `https://{platform}.api.riotgames.com/lol`,
+    /// response goes through the same status checks, retries and cache as typed methods.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use narwhalol::{LeagueClient, Region, error::ClientError};
+    ///
+    /// fn main() -> Result<(), ClientError> {
+    ///     smol::run(async {
+    ///         let lapi = LeagueClient::new(Region::NA)?;
+    ///         let status = lapi.get_raw("/status/v4/platform-data").await?;
+    ///         println!("{}", status["name"]);
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn get_raw(&self, path: &str) -> Result<serde_json::Value, ClientError> {
+        self.get(path).await
+    }
+
+    /// Same as [`get_raw`], but deserializes response into user provided type.
+    ///
+    /// Responses are cached by full url, so different types requested from
+    /// different paths never collide.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use narwhalol::{LeagueClient, Region, error::ClientError};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct PlatformData {
+    ///     id: String,
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), ClientError> {
+    ///     smol::run(async {
+    ///         let lapi = LeagueClient::new(Region::NA)?;
+    ///         let status: PlatformData = lapi.get("/status/v4/platform-data").await?;
+    ///         println!("{}", status.name);
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// [`get_raw`]: #method.get_raw
+    pub async fn get<T: Debug + DeserializeOwned + Send>(
+        &self,
+        path: &str,
+    ) -> Result<T, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/{}",
+            self.base_url,
+            path.trim_start_matches('/')
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Same as [`get`], but also tells whether the response was served from cache,
+    /// e.g. to trace individual requests.
+    ///
+    /// [`get`]: #method.get
+    pub async fn get_cached<T: Debug + DeserializeOwned + Send>(
+        &self,
+        path: &str,
+    ) -> Result<Cached<T>, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/{}",
+            self.base_url,
+            path.trim_start_matches('/')
+        ))?;
+        self.traced_resp(url).await
+    }
+
+    /// Time to keep response of the url in cache, `None` means forever
+    fn cache_ttl_for(&self, url: &Uri) -> Option<Duration> {
+        self.endpoint_cache_ttls
+            .iter()
+            .find(|(endpoint, _)| url.path().contains(endpoint.as_str()))
+            .map(|&(_, ttl)| ttl)
+            .or(self.cache_ttl)
+    }
+
+    async fn fetch(&self, url: &Uri) -> Result<String, ClientError> {
+        // Held until the body is read, released before a retry waits out its backoff.
+        // The permit is a guard, so it is also released when this future is dropped mid-flight.
+        let _permit = match &self.in_flight {
+            Some(in_flight) => Some(in_flight.acquire().await),
+            None => None,
+        };
+        let method = method_key(url);
+        if let Some(limiter) = &self.rate_limiter {
+            // Slot is reserved only when `acquire` completes, and there is no await point
+            // before the request is handed to hyper, so a dropped future never wastes one
+            limiter.acquire(&method).await;
+        }
+        // Waiting for a permit or the rate limiter does not count against the timeout,
+        // only sending the request and reading the response does
+        match self.timeout {
+            Some(timeout) => {
+                let request = self.send(url, &method);
+                futures::pin_mut!(request);
+                match future::select(request, Delay::new(timeout)).await {
+                    Either::Left((resp, _)) => resp,
+                    Either::Right(_) => Timeout {
+                        url: url.to_string(),
+                        timeout,
+                    }
+                    .fail(),
+                }
+            }
+            None => self.send(url, &method).await,
+        }
+    }
+
+    async fn send(&self, url: &Uri, method: &str) -> Result<String, ClientError> {
+        let header = HeaderValue::from_str(&self.api_key).unwrap();
+        let mut req = Request::builder()
+            .uri(url.clone())
+            .body(Body::default())
+            .unwrap();
+        *req.headers_mut() = self.default_headers.clone();
+        req.headers_mut().insert(API_KEY_HEADER, header);
+        let resp = self.client.request(req).await.context(HyperError)?;
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.update(method, resp.headers());
+        }
+        if let Some(rate_limit) = RateLimit::from_headers(resp.headers()) {
+            *self.last_rate_limit.lock() = Some(rate_limit);
+        }
+        if let Some(last_headers) = &self.last_response_headers {
+            *last_headers.lock() = Some(resp.headers().clone());
+        }
+        ClientError::check_response(
+            self.region.clone(),
+            url,
+            resp.status().as_u16(),
+            resp.headers(),
+        )?;
+        let body = resp.into_body();
+        let bytes = hyper::body::to_bytes(body).await.context(HyperError)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[async_trait]
+impl CachedClient for LeagueClient {
+    async fn cached_resp<T: Debug + DeserializeOwned + Send>(
+        &self,
+        url: Uri,
+    ) -> Result<T, ClientError> {
+        self.traced_resp(url).await.map(Cached::into_inner)
+    }
+}
+
+impl LeagueClient {
+    /// Same as `cached_resp`, but also reports whether the response came from cache.
+    async fn traced_resp<T: Debug + DeserializeOwned + Send>(
+        &self,
+        url: Uri,
+    ) -> Result<Cached<T>, ClientError> {
+        let key = cache_key(&url);
+        let maybe_resp: Option<Result<T, _>> = if self.caching {
+            self.cache
+                .get(&key)
+                .await
+                .map(|payload| serde_json::from_str(&payload))
+        } else {
+            None
+        };
+
+        if let Some(resp) = maybe_resp {
+            let resp: T = resp.context(Deserialization {
+                url: url.to_string(),
+            })?;
+            debug!("Found cached: {:?}", resp);
+            Ok(Cached {
+                value: resp,
+                from_cache: true,
+            })
+        } else {
+            debug!("Nothing in cache. Fetching from league API...");
+            // We got nothing in cache, try fetching from utl
+            let mut attempt = 0;
+            let string_response = loop {
+                match self.fetch(&url).await {
+                    Ok(body) => break body,
+                    Err(e) => match self.retry_policy.backoff(attempt, &e) {
+                        Some(delay) => {
+                            debug!("Request failed with {}, retrying in {:?}", e, delay);
+                            Delay::new(delay).await;
+                            attempt += 1;
+                        }
+                        None => return Err(e),
+                    },
+                }
+            };
+            debug!("Deserializing...");
+            let deserialized: T =
+                serde_json::from_str(&string_response).context(Deserialization {
+                    url: url.to_string(),
+                })?;
+            if self.caching {
+                let ttl = self.cache_ttl_for(&url);
+                self.cache.insert(&key, string_response, ttl).await;
+            }
+            Ok(Cached {
+                value: deserialized,
+                from_cache: false,
+            })
+        }
+    }
+}
+
+impl Default for LeagueClient {
+    fn default() -> LeagueClient {
+        LeagueClient::new(Region::default()).expect("Please provide API_KEY environment variable")
+    }
+}
+
+/// Builder for [`LeagueClient`].
+///
+/// Allows passing api key explicitly instead of reading `RIOT_API_KEY` environment variable,
+/// and sharing a [`Cache`] between several clients.
+/// # Example
+/// ```no_run
+/// use narwhalol::{LeagueClientBuilder, Region};
+///
+/// let lapi = LeagueClientBuilder::new(Region::EUW)
+///     .api_key("RGAPI-00000000-0000-0000-0000-000000000000")
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// [`LeagueClient`]: struct.LeagueClient.html
+/// [`Cache`]: ../cache/type.Cache.html
+#[derive(Debug)]
+pub struct LeagueClientBuilder {
+    region: Region,
+    api_key: Option<String>,
+    cache: Option<Cache>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    max_concurrency: Option<usize>,
+    caching: bool,
+    cache_ttl: Option<Duration>,
+    endpoint_cache_ttls: Vec<(String, Duration)>,
+    max_cache_entries: Option<usize>,
+    timeout: Option<Duration>,
+    default_headers: HeaderMap,
+    capture_response_headers: bool,
+    http_client: Option<Client>,
+    host: Option<String>,
+}
+
+impl LeagueClientBuilder {
+    /// Creates builder for the region
+    pub fn new(region: Region) -> Self {
+        LeagueClientBuilder {
+            region,
+            api_key: None,
+            cache: None,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            max_concurrency: None,
+            caching: true,
+            cache_ttl: None,
+            endpoint_cache_ttls: vec![
+                (
+                    "/spectator/v4/active-games/".to_owned(),
+                    Duration::from_secs(30),
+                ),
+                (
+                    "/spectator/v4/featured-games".to_owned(),
+                    Duration::from_secs(120),
+                ),
+            ],
+            max_cache_entries: None,
+            timeout: None,
+            default_headers: HeaderMap::new(),
+            capture_response_headers: false,
+            http_client: None,
+            host: None,
+        }
+    }
+
+    /// Riot api key to use instead of `RIOT_API_KEY` environment variable
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_owned());
+        self
+    }
+
+    /// Cache to use instead of creating an empty one
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Retry policy for failed requests, see [`RetryPolicy`]. By default requests are not retried.
+    ///
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Whether League API responses are cached, `true` by default.
+    ///
+    /// With caching disabled every call hits the network and the cache is never locked,
+    /// e.g. for short-lived processes or when fresh data is required.
+    /// Embedded [`DDragonClient`] still caches its static data.
+    ///
+    /// [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
+    pub fn caching(mut self, caching: bool) -> Self {
+        self.caching = caching;
+        self
+    }
+
+    /// Time to keep responses in cache. By default responses never expire.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Time to keep responses of endpoints whose path contains `endpoint`
+    /// (e.g. `/summoner/v4/summoners/`) in cache, overrides [`cache_ttl`].
+    ///
+    /// [`cache_ttl`]: #method.cache_ttl
+    pub fn endpoint_cache_ttl(mut self, endpoint: &str, ttl: Duration) -> Self {
+        self.endpoint_cache_ttls
+            .insert(0, (endpoint.to_owned(), ttl));
+        self
+    }
+
+    /// Maximum number of responses to keep in cache, least recently used ones are evicted first.
+    /// By default cache is unbounded. Ignored if cache is passed with [`cache`].
+    ///
+    /// [`cache`]: #method.cache
+    pub fn max_cache_entries(mut self, max_cache_entries: usize) -> Self {
+        self.max_cache_entries = Some(max_cache_entries);
+        self
+    }
+
+    /// Delays requests so they stay under app rate limits, see [`RateLimiter`].
+    /// By default requests are not limited.
+    ///
+    /// [`RateLimiter`]: ../rate_limit/struct.RateLimiter.html
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Maximum number of requests in flight at once, others wait for a free slot.
+    /// By default number of concurrent requests is not limited.
+    ///
+    /// Complements [`rate_limiter`]: it does not limit number of requests over time,
+    /// but stops batch helpers from sending all requests in one burst.
+    ///
+    /// A slot is freed as soon as its request completes or its future is dropped,
+    /// so cancelled requests never exhaust the limit.
+    ///
+    /// [`rate_limiter`]: #method.rate_limiter
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Time limit of one request including reading the body.
+    /// Requests exceeding it fail with [`Timeout`], which is retried by [`RetryPolicy`].
+    ///
+    /// [`Timeout`]: ../error/enum.ClientError.html#variant.Timeout
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Headers added to every request, e.g. a correlation id or `Accept-Language`.
+    ///
+    /// Can be called several times, later values replace earlier ones with the same name.
+    /// `X-Riot-Token` is always set to the api key and cannot be overridden.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use hyper::HeaderMap;
+    /// use narwhalol::{LeagueClientBuilder, Region};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-Correlation-Id", "narwhal-1".parse().unwrap());
+    /// let lapi = LeagueClientBuilder::new(Region::NA)
+    ///     .default_headers(headers)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        for (name, value) in headers.iter() {
+            self.default_headers.insert(name.clone(), value.clone());
+        }
+        self
+    }
+
+    /// Keeps headers of the most recent response, see [`LeagueClient::last_response_headers`].
+    /// Useful to find out which rate limit was hit. Disabled by default, as every
+    /// response then clones its headers.
+    ///
+    /// [`LeagueClient::last_response_headers`]: struct.LeagueClient.html#method.last_response_headers
+    pub fn capture_response_headers(mut self, capture: bool) -> Self {
+        self.capture_response_headers = capture;
+        self
+    }
+
+    /// Hyper client to use instead of creating a new one, e.g. with custom connection pool settings.
+    /// It is also shared with DDragon client embedded by [`with_ddragon`].
+    ///
+    /// The client has to use [`CompatConnector`] of the selected runtime.
+    ///
+    /// [`with_ddragon`]: struct.LeagueClient.html#method.with_ddragon
+    /// [`CompatConnector`]: ../types/compat/struct.CompatConnector.html
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Sends all requests to the given host (e.g. `http://127.0.0.1:8080`) instead of Riot servers.
+    ///
+    /// Both platform and regional endpoints keep their paths, so a mock server or
+    /// a caching proxy serving recorded responses can be used in tests.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use narwhalol::{LeagueClientBuilder, Region};
+    ///
+    /// let lapi = LeagueClientBuilder::new(Region::NA)
+    ///     .api_key("RGAPI-00000000-0000-0000-0000-000000000000")
+    ///     .host("http://127.0.0.1:8080")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(lapi.base_url(), "http://127.0.0.1:8080/lol");
+    /// ```
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_owned());
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// Returns [`NoToken`] if no api key was provided and `RIOT_API_KEY` is not set.
+    ///
+    /// [`NoToken`]: ../error/enum.ClientError.html#variant.NoToken
+    pub fn build(self) -> Result<LeagueClient, ClientError> {
+        let region = self.region;
+        let host = self.host.map(|host| host.trim_end_matches('/').to_owned());
+        let (platform_url, regional_url, account_url) = api_urls(&region, host.as_deref());
+        let base_url = format!("{}/lol", platform_url);
+        let api_key = match self.api_key {
+            Some(api_key) => api_key,
+            None => env::var("RIOT_API_KEY").context(NoToken {})?,
+        };
+        check_token(&api_key)?;
+        let client = self.http_client.unwrap_or_else(construct_hyper_client);
+        let max_cache_entries = self.max_cache_entries;
+        let cache: Cache = self.cache.unwrap_or_else(|| {
+            let cache = match max_cache_entries {
+                Some(max_entries) => ResponseCache::with_max_entries(max_entries),
+                None => ResponseCache::new(),
+            };
+            Arc::new(Mutex::new(cache)) as Cache
+        });
+        Ok(LeagueClient {
+            region,
+            host,
+            base_url,
+            platform_url,
+            regional_url,
+            account_url,
+            ddragon: None,
+            cache,
+            client,
+            api_key,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            in_flight: self.max_concurrency.map(Semaphore::new),
+            caching: self.caching,
+            cache_ttl: self.cache_ttl,
+            endpoint_cache_ttls: self.endpoint_cache_ttls,
+            timeout: self.timeout,
+            default_headers: self.default_headers,
+            last_rate_limit: Mutex::new(None),
+            last_response_headers: if self.capture_response_headers {
+                Some(Mutex::new(None))
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// Platform, regional and account urls of the region, or `host` for all of them if it is set.
+/// Hosts are lowercased, as Riot documents them
+fn api_urls(region: &Region, host: Option<&str>) -> (String, String, String) {
+    match host {
+        Some(host) => (host.to_owned(), host.to_owned(), host.to_owned()),
+        None => (
+            format!(
+                "https://{}.api.riotgames.com",
+                region.as_platform_str().to_ascii_lowercase()
+            ),
+            format!("https://{}.api.riotgames.com", region.as_regional_str()),
+            format!(
+                "https://{}.api.riotgames.com",
+                region.as_account_regional_str()
+            ),
+        ),
+    }
+}
+
+/// Riot only accepts api key in this header, `?api_key=` query parameter is deprecated
+const API_KEY_HEADER: &str = "X-Riot-Token";
+
+/// Length of `RGAPI-` prefix followed by a uuid
+const MIN_TOKEN_LEN: usize = 42;
+
+/// Rejects only clearly malformed tokens, riot may change key format at any time.
+fn check_token(token: &str) -> Result<(), ClientError> {
+    ensure!(
+        !token.is_empty() && token.chars().all(|c| c.is_ascii_graphic()),
+        WrongToken {
+            token: redact_token(token)
+        }
+    );
+    if !token.starts_with("RGAPI-") || token.len() < MIN_TOKEN_LEN {
+        warn!(
+            "Provided token {} does not look like riot api token",
+            redact_token(token)
+        );
+    }
+    Ok(())
+}
+
+/// Keeps only a short prefix (at most `RGAPI-`) and the length of the token,
+/// so it can be shown in logs and errors without leaking the key
+fn redact_token(token: &str) -> String {
+    let len = token.chars().count();
+    let prefix: String = token.chars().take((len / 3).min(6)).collect();
+    format!("{}... ({} chars)", prefix.escape_debug(), len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_token, redact_token, LeagueClient, LeagueClientBuilder, MasterySort, MatchIdQuery,
+        RetryPolicy, API_KEY_HEADER,
+    };
+    use crate::constants::{LanguageCode, RankedQueue, RankedTier, Region};
+    use crate::rate_limit::RateLimiter;
+
+    use futures::prelude::*;
+    use futures::{Future, FutureExt, TryFutureExt};
+    use pretty_env_logger;
+
+    use crate::cache::{Cache, Cached};
+    use crate::constants::division::Division;
+    use crate::ddragon::DDRAGON_HOST;
+    use crate::dto::api::{ChampionInfo, ChampionMastery, CurrentGameInfo, Summoner};
+    use crate::dto::ddragon::ChampionFullData;
+    use crate::error::ClientError;
+    use crate::types::compat::CompatConnector;
+    #[cfg(not(feature = "tokio_rt"))]
+    use crate::types::compat::CompatExecutor;
+    use crate::utils::mock::{MockResponse, MockServer};
+    use futures_timer::Delay;
+    use hyper::HeaderMap;
+    use log::debug;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    const TEST_KEY: &str = "RGAPI-00000000-0000-0000-0000-000000000000";
+    const SUMMONER_JSON: &str = r#"{
+        "profileIconId": 1,
+        "name": "Vetro",
+        "puuid": "test-puuid",
+        "summonerLevel": 30,
+        "revisionDate": 1589999999000,
+        "id": "test-id",
+        "accountId": "test-account-id"
+    }"#;
+
+    #[cfg(test)]
+    fn print_cache(cache: Cache) {
+        debug!("{:?}", cache)
+    }
+
+    fn mock_client(server: &MockServer) -> LeagueClientBuilder {
+        LeagueClientBuilder::new(Region::NA)
+            .api_key(TEST_KEY)
+            .host(server.url())
+    }
+
+    #[test]
+    fn sends_platform_and_regional_requests_to_host() {
+        let server = MockServer::start(vec![MockResponse::new(200, "[]")]);
+        smol::run(async {
+            let lapi = LeagueClientBuilder::new(Region::EUW)
+                .api_key(TEST_KEY)
+                .host(&format!("{}/", server.url()))
+                .build()
+                .unwrap();
+            lapi.get_league_entries_by_summoner("test-id")
+                .await
+                .unwrap();
+            lapi.get_match_ids_by_puuid("test-puuid", None, None)
+                .await
+                .unwrap();
+            let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+            assert_eq!(
+                paths,
+                vec![
+                    "/lol/league/v4/entries/by-summoner/test-id",
+                    "/lol/match/v5/matches/by-puuid/test-puuid/ids?start=0&count=20",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn builds_match_id_query_string() {
+        assert_eq!(MatchIdQuery::new().query_string(), "");
+        let query = MatchIdQuery::new()
+            .count(100)
+            .queue(420)
+            .match_type("ranked")
+            .start_time(1_623_801_600)
+            .end_time(1_624_406_400)
+            .start(0);
+        assert_eq!(
+            query.query_string(),
+            "queue=420&type=ranked&startTime=1623801600&endTime=1624406400&start=0&count=100"
+        );
+    }
+
+    #[test]
+    fn switches_region_in_place() {
+        let mut lapi = LeagueClientBuilder::new(Region::NA)
+            .api_key(TEST_KEY)
+            .build()
+            .unwrap();
+        assert_eq!(lapi.region(), Region::NA);
+        lapi.set_region(Region::EUW);
+        assert_eq!(lapi.region(), Region::EUW);
+        assert_eq!(lapi.base_url(), "https://euw1.api.riotgames.com/lol");
+        assert_eq!(lapi.regional_url, "https://europe.api.riotgames.com");
+    }
+
+    #[test]
+    fn routes_oce_accounts_to_asia() {
+        let lapi = LeagueClientBuilder::new(Region::OCE)
+            .api_key(TEST_KEY)
+            .build()
+            .unwrap();
+        assert_eq!(lapi.regional_url, "https://americas.api.riotgames.com");
+        assert_eq!(lapi.account_url, "https://asia.api.riotgames.com");
+    }
+
+    #[test]
+    fn keeps_custom_host_when_switching_region() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let mut lapi = mock_client(&server).build().unwrap();
+            lapi.set_region(Region::EUW);
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/summoner/v4/summoners/by-puuid/test-puuid"
+            );
+            assert_eq!(lapi.base_url(), format!("{}/lol", server.url()));
+        })
+    }
+
+    #[test]
+    fn sends_match_id_query_to_regional_host() {
+        let server = MockServer::start(vec![MockResponse::new(200, r#"["NA1_1"]"#)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let query = MatchIdQuery::new().match_type("ranked").count(5);
+            let ids = lapi
+                .get_match_ids_with_query("test-puuid", &query)
+                .await
+                .unwrap();
+            assert_eq!(ids, vec!["NA1_1"]);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/match/v5/matches/by-puuid/test-puuid/ids?type=ranked&count=5"
+            );
+        })
+    }
+
+    #[test]
+    fn retries_on_server_errors() {
+        let server = MockServer::start(vec![
+            MockResponse::new(503, ""),
+            MockResponse::new(503, ""),
+            MockResponse::new(200, SUMMONER_JSON),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .retry_policy(RetryPolicy::new(2, Duration::from_millis(10)))
+                .build()
+                .unwrap();
+            let sum = lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(&sum.name, "Vetro");
+            assert_eq!(server.requests().len(), 3);
+        })
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let server = MockServer::start(vec![
+            MockResponse::new(404, ""),
+            MockResponse::new(200, SUMMONER_JSON),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .retry_policy(RetryPolicy::new(2, Duration::from_millis(10)))
+                .build()
+                .unwrap();
+            let err = lapi.get_summoner_by_puuid("test-puuid").await.unwrap_err();
+            assert!(matches!(err, ClientError::DataNotFound { .. }));
+            assert_eq!(server.requests().len(), 1);
+        })
+    }
+
+    #[test]
+    fn limits_requests_in_flight() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, SUMMONER_JSON).delay(Duration::from_millis(20))
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).max_concurrency(5).build().unwrap();
+            let puuids: Vec<_> = (0..100).map(|i| format!("puuid-{}", i)).collect();
+            let results = futures::future::join_all(
+                puuids.iter().map(|puuid| lapi.get_summoner_by_puuid(puuid)),
+            )
+            .await;
+            assert!(results.iter().all(Result::is_ok));
+            assert_eq!(server.requests().len(), 100);
+            assert!(server.peak_concurrency() <= 5);
+        })
+    }
+
+    #[test]
+    fn releases_permits_of_dropped_requests() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, SUMMONER_JSON).delay(Duration::from_millis(20))
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).max_concurrency(2).build().unwrap();
+            let puuids: Vec<_> = (0..20).map(|i| format!("puuid-{}", i)).collect();
+            for puuid in &puuids {
+                // Polled once: either holds a permit waiting for response or waits for one
+                assert!(lapi.get_summoner_by_puuid(puuid).now_or_never().is_none());
+            }
+            let request = Box::pin(lapi.get_summoner_by_puuid("test-puuid"));
+            match future::select(request, Delay::new(Duration::from_secs(5))).await {
+                future::Either::Left((summoner, _)) => assert!(summoner.is_ok()),
+                future::Either::Right(_) => panic!("permits of dropped requests were not released"),
+            };
+        })
+    }
+
+    #[test]
+    fn queued_requests_do_not_time_out() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, SUMMONER_JSON).delay(Duration::from_millis(100))
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .max_concurrency(1)
+                .timeout(Duration::from_millis(300))
+                .build()
+                .unwrap();
+            let puuids: Vec<_> = (0..4).map(|i| format!("puuid-{}", i)).collect();
+            // The last request waits for a permit longer than the timeout
+            let results = futures::future::join_all(
+                puuids.iter().map(|puuid| lapi.get_summoner_by_puuid(puuid)),
+            )
+            .await;
+            assert!(results.iter().all(Result::is_ok));
+            assert_eq!(server.peak_concurrency(), 1);
+        })
+    }
+
+    #[test]
+    fn gets_summoners_by_names_concurrently() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let summoners = lapi
+                .get_summoners_by_names(&["first", "second", "third"])
+                .await;
+            assert_eq!(summoners.len(), 3);
+            assert!(summoners.iter().all(Result::is_ok));
+            assert_eq!(server.requests().len(), 3);
+        })
+    }
+
+    #[test]
+    fn does_not_cache_error_responses() {
+        let server = MockServer::start(vec![
+            MockResponse::new(
+                404,
+                r#"{"status":{"message":"Data not found","status_code":404}}"#,
+            ),
+            MockResponse::new(200, SUMMONER_JSON),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let err = lapi.get_summoner_by_puuid("test-puuid").await.unwrap_err();
+            let expected_url = format!(
+                "{}/summoner/v4/summoners/by-puuid/test-puuid",
+                lapi.base_url()
+            );
+            assert!(
+                matches!(err, ClientError::DataNotFound { url: Some(ref url) } if *url == expected_url)
+            );
+            assert_eq!(lapi.cache_stats().entries, 0);
+            let sum = lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(&sum.name, "Vetro");
+            assert_eq!(lapi.cache_stats().entries, 1);
+            assert_eq!(server.requests().len(), 2);
+        })
+    }
+
+    #[test]
+    fn returns_error_on_malformed_json() {
+        let server = MockServer::start(vec![MockResponse::new(200, r#"{"name": "Vetro""#)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let err = lapi.get_summoner_by_puuid("test-puuid").await.unwrap_err();
+            assert!(matches!(err, ClientError::Deserialization { .. }));
+            assert_eq!(lapi.cache_stats().entries, 0);
+        })
+    }
+
+    #[test]
+    fn times_out_slow_requests() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, SUMMONER_JSON).delay(Duration::from_millis(500))
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .timeout(Duration::from_millis(100))
+                .build()
+                .unwrap();
+            let err = lapi.get_summoner_by_puuid("test-puuid").await.unwrap_err();
+            assert!(matches!(err, ClientError::Timeout { .. }));
+        })
+    }
+
+    #[test]
+    fn rate_limit_wait_does_not_count_against_timeout() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .rate_limiter(RateLimiter::new(&[(1, Duration::from_millis(400))]))
+                .timeout(Duration::from_millis(200))
+                .build()
+                .unwrap();
+            let now = Instant::now();
+            lapi.get_summoner_by_puuid("first").await.unwrap();
+            lapi.get_summoner_by_puuid("second").await.unwrap();
+            assert!(now.elapsed() >= Duration::from_millis(350));
+        })
+    }
+
+    #[test]
+    fn rate_limiter_delays_requests() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)
+            .header("X-App-Rate-Limit", "2:1")
+            .header("X-App-Rate-Limit-Count", "1:1")]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .rate_limiter(RateLimiter::new(&[(2, Duration::from_secs(1))]))
+                .build()
+                .unwrap();
+            let now = Instant::now();
+            for puuid in &["first", "second", "third"] {
+                lapi.get_summoner_by_puuid(puuid).await.unwrap();
+            }
+            assert!(now.elapsed() >= Duration::from_millis(900));
+            assert_eq!(server.requests().len(), 3);
+        })
+    }
+
+    #[test]
+    fn rate_limits_methods_independently() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)
+            .header("X-Method-Rate-Limit", "1:10")
+            .header("X-Method-Rate-Limit-Count", "1:10")]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .rate_limiter(RateLimiter::new(&[]))
+                .build()
+                .unwrap();
+            let now = Instant::now();
+            // Each endpoint has used up its own budget only
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            lapi.get_summoner_by_name("Vetro").await.unwrap();
+            assert!(now.elapsed() < Duration::from_secs(1));
+            assert_eq!(server.requests().len(), 2);
+        })
+    }
+
+    #[test]
+    fn remembers_last_rate_limit() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)
+            .header("X-App-Rate-Limit", "20:1,100:120")
+            .header("X-App-Rate-Limit-Count", "1:1,1:120")
+            .header("X-Method-Rate-Limit", "2000:60")
+            .header("X-Method-Rate-Limit-Count", "1:60")]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            assert!(lapi.last_rate_limit().is_none());
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            let rate_limit = lapi.last_rate_limit().unwrap();
+            assert_eq!(rate_limit.app.len(), 2);
+            assert_eq!(rate_limit.app[1].remaining(), 99);
+            assert_eq!(rate_limit.method[0].remaining(), 1999);
+        })
+    }
+
+    #[test]
+    fn captures_last_response_headers() {
+        let server = MockServer::start(vec![MockResponse::new(429, "")
+            .header("X-Rate-Limit-Type", "method")
+            .header("X-Method-Rate-Limit-Count", "2001:60")]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap_err();
+            assert!(lapi.last_response_headers().is_none());
+
+            let lapi = mock_client(&server)
+                .capture_response_headers(true)
+                .build()
+                .unwrap();
+            assert!(lapi.last_response_headers().is_none());
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap_err();
+            let headers = lapi.last_response_headers().unwrap();
+            assert_eq!(headers["x-rate-limit-type"], "method");
+            assert_eq!(headers["x-method-rate-limit-count"], "2001:60");
+        })
+    }
+
+    #[test]
+    fn refetches_expired_cache_entries() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .endpoint_cache_ttl("/summoner/v4/", Duration::from_millis(50))
+                .build()
+                .unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(server.requests().len(), 1);
+            Delay::new(Duration::from_millis(100)).await;
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(server.requests().len(), 2);
+        })
+    }
+
+    #[test]
+    fn does_not_cache_unexpected_statuses() {
+        let server = MockServer::start(vec![MockResponse::new(418, r#"{"name": "teapot"}"#)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            for _ in 0..2 {
+                let err = lapi
+                    .get_raw("/summoner/v4/summoners/by-puuid/test-puuid")
+                    .await
+                    .unwrap_err();
+                assert!(matches!(
+                    err,
+                    ClientError::UnexpectedStatus { status: 418, .. }
+                ));
+            }
+            assert_eq!(server.requests().len(), 2);
+        })
+    }
+
+    #[test]
+    fn gets_raw_json() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let raw = lapi
+                .get_raw("/summoner/v4/summoners/by-puuid/test-puuid")
+                .await
+                .unwrap();
+            assert_eq!(raw["name"], "Vetro");
+            lapi.get_raw("summoner/v4/summoners/by-puuid/test-puuid")
+                .await
+                .unwrap();
+            let requests = server.requests();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(
+                requests[0].path,
+                "/lol/summoner/v4/summoners/by-puuid/test-puuid"
+            );
+            assert_eq!(requests[0].header("x-riot-token"), Some(TEST_KEY));
+        })
+    }
+
+    #[test]
+    fn sends_api_key_in_header() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            let request = &server.requests()[0];
+            assert_eq!(
+                request.header(&API_KEY_HEADER.to_ascii_lowercase()),
+                Some(TEST_KEY)
+            );
+            assert!(!request.path.contains("api_key"));
+        })
+    }
+
+    #[test]
+    fn sends_default_headers() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Correlation-Id", "narwhal-1".parse().unwrap());
+        headers.insert(API_KEY_HEADER, "RGAPI-ignored".parse().unwrap());
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .default_headers(headers)
+                .build()
+                .unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            let request = &server.requests()[0];
+            assert_eq!(request.header("x-correlation-id"), Some("narwhal-1"));
+            assert_eq!(request.header("x-riot-token"), Some(TEST_KEY));
+        })
+    }
+
+    #[test]
+    fn uses_rotated_api_key() {
+        const NEW_KEY: &str = "RGAPI-11111111-1111-1111-1111-111111111111";
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let mut lapi = mock_client(&server).build().unwrap();
+            lapi.get_summoner_by_puuid("first").await.unwrap();
+            assert!(lapi.set_api_key("not a key").is_err());
+            lapi.set_api_key(NEW_KEY).unwrap();
+            lapi.get_summoner_by_puuid("second").await.unwrap();
+            let requests = server.requests();
+            assert_eq!(requests[0].header("x-riot-token"), Some(TEST_KEY));
+            assert_eq!(requests[1].header("x-riot-token"), Some(NEW_KEY));
+        })
+    }
+
+    #[test]
+    fn gets_user_defined_type() {
+        #[derive(Debug, serde::Deserialize)]
+        struct ShortSummoner {
+            name: String,
+        }
+
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let sum: ShortSummoner = lapi
+                .get("/summoner/v4/summoners/by-puuid/test-puuid")
+                .await
+                .unwrap();
+            assert_eq!(&sum.name, "Vetro");
+            // Same url is served from cache whatever type is requested
+            let full: Summoner = lapi
+                .get("/summoner/v4/summoners/by-puuid/test-puuid")
+                .await
+                .unwrap();
+            assert_eq!(&full.puuid, "test-puuid");
+            assert_eq!(server.requests().len(), 1);
+        })
+    }
+
+    #[test]
+    fn reports_whether_response_was_cached() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let path = "/summoner/v4/summoners/by-puuid/test-puuid";
+            let first: Cached<Summoner> = lapi.get_cached(path).await.unwrap();
+            assert!(!first.from_cache);
+            let second: Cached<Summoner> = lapi.get_cached(path).await.unwrap();
+            assert!(second.from_cache);
+            assert_eq!(first.value.puuid, second.value.puuid);
+            assert_eq!(server.requests().len(), 1);
+        })
+    }
+
+    #[test]
+    fn fetches_every_time_without_caching() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).caching(false).build().unwrap();
+            let path = "/summoner/v4/summoners/by-puuid/test-puuid";
+            let first: Cached<Summoner> = lapi.get_cached(path).await.unwrap();
+            let second: Cached<Summoner> = lapi.get_cached(path).await.unwrap();
+            assert!(!first.from_cache && !second.from_cache);
+            assert_eq!(server.requests().len(), 2);
+            assert_eq!(lapi.cache_stats().entries, 0);
+        })
+    }
+
+    #[test]
+    fn uses_injected_http_client() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        let mut builder = hyper::Client::builder();
+        builder.pool_max_idle_per_host(1);
+        #[cfg(not(feature = "tokio_rt"))]
+        builder.executor(CompatExecutor);
+        let client = Arc::new(builder.build(CompatConnector::new()));
+        smol::run(async {
+            let lapi = mock_client(&server)
+                .http_client(client.clone())
+                .build()
+                .unwrap();
+            assert!(Arc::ptr_eq(&lapi.client, &client));
+            let sum = lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(&sum.name, "Vetro");
+        })
+    }
+
+    #[test]
+    fn counts_cache_hits_and_misses() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            lapi.clear_cache().await;
+            assert_eq!(lapi.cache_stats().entries, 0);
+            lapi.get_summoner_by_puuid("test-puuid").await.unwrap();
+            let stats = lapi.cache_stats();
+            assert_eq!(stats.hits, 1);
+            assert_eq!(stats.misses, 2);
+            assert_eq!(stats.entries, 1);
+        })
+    }
+
+    #[test]
+    fn builds_with_explicit_api_key() {
+        let lapi = LeagueClientBuilder::new(Region::EUW)
+            .api_key(TEST_KEY)
+            .build()
+            .unwrap();
+        assert_eq!(&lapi.api_key, TEST_KEY);
+        assert!(LeagueClientBuilder::new(Region::EUW)
+            .api_key("not a key")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn accepts_dev_and_production_tokens() {
+        assert!(check_token("RGAPI-0a1b2c3d-4e5f-6789-abcd-ef0123456789").is_ok());
+        assert!(check_token("RGAPI-prod-0a1b2c3d-4e5f-6789-abcd-ef0123456789-x1").is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        for token in &["", "   ", "RGAPI-0a1b2c3d 4e5f\n"] {
+            let err = check_token(token).unwrap_err();
+            assert!(matches!(err, ClientError::WrongToken { .. }));
+        }
+    }
+
+    #[test]
+    fn redacts_token_in_errors() {
+        let token = "RGAPI-0a1b2c3d-4e5f-6789-abcd ef0123456789";
+        let err = check_token(token).unwrap_err();
+        let message = err.to_string();
+        assert!(!message.contains("0a1b2c3d"));
+        assert!(message.contains("RGAPI-... (42 chars)"));
+        assert_eq!(redact_token("short"), "s... (5 chars)");
+    }
+
+    #[test]
+    fn try_ddragon_returns_none_without_ddragon() {
+        let mut lapi = LeagueClientBuilder::new(Region::EUW)
+            .api_key(TEST_KEY)
+            .build()
+            .unwrap();
+        assert!(lapi.try_ddragon().is_none())
+    }
+
+    #[test]
+    fn gets_summoner_data() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            pretty_env_logger::try_init().unwrap_or(());
+            let lapi = mock_client(&server).build().unwrap();
+            let sum = lapi.get_summoner_by_name("Vetro").await.unwrap();
+            assert_eq!(&sum.account_id, "test-account-id");
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/summoner/v4/summoners/by-name/Vetro"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_summoner_with_spaces_in_name() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            lapi.get_summoner_by_name("Hide on bush").await.unwrap();
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/summoner/v4/summoners/by-name/Hide%20on%20bush"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_summoner_by_puuid() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let by_name = lapi.get_summoner_by_name("Vetro").await.unwrap();
+            let by_puuid = lapi.get_summoner_by_puuid(&by_name.puuid).await.unwrap();
+            assert_eq!(&by_puuid.name, "Vetro");
+            assert_eq!(
+                server.requests()[1].path,
+                "/lol/summoner/v4/summoners/by-puuid/test-puuid"
+            );
+        })
+    }
+
+    #[test]
+    fn lapi_caches_properly() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        smol::run(async {
+            let cli = mock_client(&server).build().unwrap();
+            let cache = cli.cache.clone();
+            let first = cli.get_summoner_by_name("Vetro").await.unwrap();
+            let second = cli.get_summoner_by_name("Vetro").await.unwrap();
+            assert_eq!(first.puuid, second.puuid);
+            assert_eq!(server.requests().len(), 1);
+            print_cache(cache);
+        })
+    }
+
+    #[test]
+    fn gets_champion_info() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"{"freeChampionIds":[1,64,103],"freeChampionIdsForNewPlayers":[18,81],"maxNewPlayerLevel":10}"#,
+        )]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let champ_info = lapi.get_champion_info().await.unwrap();
+            assert_eq!(champ_info.free_champion_ids, vec![1, 64, 103]);
+            assert_eq!(champ_info.free_champion_ids_for_new_players, vec![18, 81]);
+            assert_eq!(champ_info.max_new_player_level, 10);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/platform/v3/champion-rotations"
+            );
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_free_rotation_champions() {
+        smol::run(async {
+            let mut lapi = LeagueClient::new(Region::default())
+                .unwrap()
+                .with_ddragon(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let champions = lapi.get_free_rotation_champions().await.unwrap();
+            assert!(champions.len() > 10);
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_champion_rotation() {
+        smol::run(async {
+            let mut lapi = LeagueClient::new(Region::default())
+                .unwrap()
+                .with_ddragon(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let info = lapi.get_champion_info().await.unwrap();
+            let rotation = lapi.get_champion_rotation().await.unwrap();
+            assert_eq!(rotation.regular.len(), info.free_champion_ids.len());
+            assert!(rotation.regular.iter().all(|c| !c.name.is_empty()));
+            assert_eq!(
+                rotation.beginner.len(),
+                info.free_champion_ids_for_new_players.len()
+            );
+            assert_eq!(
+                i64::from(rotation.beginner_max_level),
+                info.max_new_player_level
+            );
+        })
+    }
+
+    #[test]
+    fn free_rotation_champions_require_ddragon() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"{"freeChampionIds":[],"freeChampionIdsForNewPlayers":[],"maxNewPlayerLevel":10}"#,
+        )]);
         smol::run(async {
-            let mut cli = LeagueClient::new(Region::RU).unwrap();
-            let cache = cli.cache.clone();
-            let _ = cli.get_summoner_by_name("Vetro").await.unwrap();
-            let now = Instant::now();
-            let _ = cli.get_summoner_by_name("Vetro").await.unwrap();
-            assert!(now.elapsed().as_millis() <= 2);
-            print_cache(cache);
+            let mut lapi = mock_client(&server).build().unwrap();
+            let err = lapi.get_free_rotation_champions().await.unwrap_err();
+            assert!(matches!(err, ClientError::DDragonNotAttached));
         })
     }
 
     #[test]
-    fn gets_champion_info() {
+    fn returns_error_on_unparseable_url() {
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
         smol::run(async {
-            let mut lapi = LeagueClient::new(Region::default()).unwrap();
-            let champ_info = lapi.get_champion_info().await.unwrap();
-            assert!(champ_info.free_champion_ids.len() > 10);
-            assert!(champ_info.free_champion_ids_for_new_players.len() > 0);
-            assert_ne!(champ_info.max_new_player_level, 0)
+            let lapi = mock_client(&server).build().unwrap();
+            let err = lapi.get_summoner_by_puuid("bad\npuuid").await.unwrap_err();
+            assert!(matches!(err, ClientError::UrlNotParsed));
+            assert!(server.requests().is_empty());
+        })
+    }
+
+    #[test]
+    fn pins_embedded_ddragon_version() {
+        let mut lapi = LeagueClientBuilder::new(Region::NA)
+            .api_key(TEST_KEY)
+            .build()
+            .unwrap()
+            .with_ddragon_version(LanguageCode::UNITED_STATES, "10.10.3216176");
+        let ddragon = lapi.ddragon();
+        assert_eq!(ddragon.version(), "10.10.3216176");
+        assert_eq!(ddragon.host(), DDRAGON_HOST);
+    }
+
+    #[test]
+    fn enriching_masteries_requires_ddragon() {
+        smol::run(async {
+            let mut lapi = LeagueClientBuilder::new(Region::NA)
+                .api_key(TEST_KEY)
+                .build()
+                .unwrap();
+            let err = lapi.enrich_masteries(Vec::new()).await.unwrap_err();
+            assert!(matches!(err, ClientError::DDragonNotAttached));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn enriches_masteries() {
+        smol::run(async {
+            let mut lapi = LeagueClient::new(Region::NA)
+                .unwrap()
+                .with_ddragon(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            let masteries = lapi.get_champion_masteries(&summoner.id).await.unwrap();
+            let named = lapi.enrich_masteries(masteries.clone()).await.unwrap();
+            assert_eq!(named.len(), masteries.len());
+            assert!(named
+                .iter()
+                .all(|m| !m.champion_name.is_empty() && m.square_icon_url.ends_with(".png")));
         })
     }
 
     #[test]
     fn gets_champion_masteries() {
+        let masteries = r#"[
+            {
+                "championId": 64,
+                "championLevel": 7,
+                "championPoints": 93748,
+                "lastPlayTime": 1589999999000,
+                "championPointsSinceLastLevel": 72148,
+                "championPointsUntilNextLevel": 0,
+                "chestGranted": true,
+                "tokensEarned": 0,
+                "summonerId": "test-id"
+            },
+            {
+                "championId": 103,
+                "championLevel": 5,
+                "championPoints": 40000,
+                "lastPlayTime": 1589000000000,
+                "championPointsSinceLastLevel": 18400,
+                "championPointsUntilNextLevel": 0,
+                "chestGranted": false,
+                "tokensEarned": 1,
+                "summonerId": "test-id"
+            }
+        ]"#;
+        let server = MockServer::start(vec![MockResponse::new(200, masteries)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let masteries = lapi.get_champion_masteries("test-id").await.unwrap();
+            assert_eq!(masteries.len(), 2);
+            assert_eq!(masteries[0].champion_id, 64);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/champion-mastery/v4/champion-masteries/by-summoner/test-id"
+            );
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn counts_mastery_levels() {
         smol::run(async {
-            let mut lapi = LeagueClient::new(Region::NA).unwrap();
+            let lapi = LeagueClient::new(Region::NA).unwrap();
             let summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
             let masteries = lapi.get_champion_masteries(&summoner.id).await.unwrap();
-            assert_ne!(masteries.len(), 0)
+            let counts = lapi.get_mastery_level_counts(&summoner.id).await.unwrap();
+            assert_eq!(counts.values().sum::<usize>(), masteries.len());
+            assert!(counts[&7] > 0);
+        })
+    }
+
+    #[test]
+    fn gets_top_champion_masteries() {
+        let masteries = r#"[
+            {
+                "championId": 64,
+                "championLevel": 7,
+                "championPoints": 93748,
+                "lastPlayTime": 1589999999000,
+                "championPointsSinceLastLevel": 72148,
+                "championPointsUntilNextLevel": 0,
+                "chestGranted": true,
+                "tokensEarned": 0,
+                "summonerId": "test-id"
+            },
+            {
+                "championId": 103,
+                "championLevel": 5,
+                "championPoints": 40000,
+                "lastPlayTime": 1589000000000,
+                "championPointsSinceLastLevel": 18400,
+                "championPointsUntilNextLevel": 0,
+                "chestGranted": false,
+                "tokensEarned": 1,
+                "summonerId": "test-id"
+            }
+        ]"#;
+        let server = MockServer::start(vec![MockResponse::new(200, masteries)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let masteries = lapi.get_top_champion_masteries("test-id", 3).await.unwrap();
+            assert!(masteries.len() <= 3);
+            assert!(masteries
+                .windows(2)
+                .all(|w| w[0].champion_points >= w[1].champion_points));
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/champion-mastery/v4/champion-masteries/by-summoner/test-id/top?count=3"
+            );
         })
     }
 
     #[test]
     fn gets_champion_mastery_by_id() {
+        let mastery = r#"{
+            "championId": 64,
+            "championLevel": 7,
+            "championPoints": 93748,
+            "lastPlayTime": 1589999999000,
+            "championPointsSinceLastLevel": 72148,
+            "championPointsUntilNextLevel": 0,
+            "chestGranted": true,
+            "tokensEarned": 0,
+            "summonerId": "test-id"
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(200, mastery)]);
         smol::run(async {
-            let mut lapi = LeagueClient::new(Region::default())
-                .unwrap()
-                .with_ddragon(LanguageCode::UNITED_STATES)
-                .await;
-            let mut ddragon_client = lapi.ddragon();
-            let lee_sin: ChampionFullData = ddragon_client.get_champion("LeeSin").await.unwrap();
-            let summoner: Summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            let lapi = mock_client(&server).build().unwrap();
             let mastery: ChampionMastery = lapi
-                .get_champion_mastery_by_id(&summoner.id, lee_sin.key.parse().unwrap())
+                .get_champion_mastery_by_id("test-id", 64)
                 .await
                 .unwrap();
-
             assert_eq!(mastery.champion_id, 64);
             assert_eq!(mastery.champion_level, 7);
-            assert!(mastery.champion_points >= 93748)
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/champion-mastery/v4/champion-masteries/by-summoner/test-id/by-champion/64"
+            );
         })
     }
 
     #[test]
-    fn gets_total_mastery_score() {
+    fn sorts_and_filters_champion_masteries() {
+        let mastery = |champion_id: i64, level: i32, points: i32, last_play_time: i64| {
+            format!(
+                r#"{{"chestGranted": false, "championLevel": {}, "championPoints": {},
+                "championId": {}, "championPointsUntilNextLevel": 0, "lastPlayTime": {},
+                "tokensEarned": 0, "championPointsSinceLastLevel": 0, "summonerId": "test-id"}}"#,
+                level, points, champion_id, last_play_time
+            )
+        };
+        let body = format!(
+            "[{},{},{}]",
+            mastery(1, 5, 30_000, 300),
+            mastery(2, 7, 20_000, 100),
+            mastery(3, 4, 90_000, 200)
+        );
+        let server = MockServer::start(vec![MockResponse::new(200, &body)]);
         smol::run(async {
-            let mut lapi = LeagueClient::new(Region::default())
-                .map_err(|e| {
-                    println!("{}", e);
-                    e
-                })
+            let lapi = mock_client(&server).build().unwrap();
+            let ids = |masteries: Vec<ChampionMastery>| {
+                masteries.iter().map(|m| m.champion_id).collect::<Vec<_>>()
+            };
+            let by_points = lapi
+                .get_champion_masteries_sorted("test-id", MasterySort::Points, None)
+                .await
+                .unwrap();
+            assert_eq!(ids(by_points), vec![3, 1, 2]);
+            let by_level = lapi
+                .get_champion_masteries_sorted("test-id", MasterySort::Level, None)
+                .await
                 .unwrap();
-            let summoner: Summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            assert_eq!(ids(by_level), vec![2, 1, 3]);
+            let recent = lapi
+                .get_champion_masteries_sorted("test-id", MasterySort::LastPlayTime, Some(5))
+                .await
+                .unwrap();
+            assert_eq!(ids(recent), vec![1, 2]);
+            assert_eq!(server.requests().len(), 1);
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_mastery_summary() {
+        smol::run(async {
+            let lapi = LeagueClient::new(Region::NA).unwrap();
+            let summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            let summary = lapi.get_mastery_summary(&summoner.id).await.unwrap();
+            let masteries = lapi.get_champion_masteries(&summoner.id).await.unwrap();
             let score = lapi.get_total_mastery_score(&summoner.id).await.unwrap();
-            assert!(score >= 192)
+            assert_eq!(summary.total_score, score);
+            assert_eq!(summary.champion_count, masteries.len());
+            assert_eq!(summary.top_champions.len(), masteries.len().min(3));
+            assert_eq!(summary.top_champions[0], masteries[0]);
+        })
+    }
+
+    #[test]
+    fn gets_total_mastery_score() {
+        let server = MockServer::start(vec![MockResponse::new(200, "192")]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let score = lapi.get_total_mastery_score("test-id").await.unwrap();
+            assert_eq!(score, 192);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/champion-mastery/v4/scores/by-summoner/test-id"
+            );
         })
     }
 
     #[test]
     fn gets_league_exp() -> Result<(), ClientError> {
+        let entry = r#"{
+            "queueType": "RANKED_SOLO_5x5",
+            "summonerName": "Vetro",
+            "hotStreak": false,
+            "wins": 100,
+            "veteran": true,
+            "losses": 90,
+            "rank": "I",
+            "tier": "CHALLENGER",
+            "inactive": false,
+            "freshBlood": false,
+            "leagueId": "test-league",
+            "summonerId": "test-id",
+            "leaguePoints": 1000
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(200, &format!("[{}]", entry))]);
         smol::run(async {
-            let mut lapi = LeagueClient::new(Region::default()).unwrap();
+            let lapi = mock_client(&server).build().unwrap();
             let challengers = lapi
                 .get_league_exp_entries(
                     RankedQueue::SOLO,
@@ -377,10 +2680,726 @@ mod tests {
                     Division::I,
                     None,
                 )
+                .await?;
+            assert_eq!(challengers.len(), 1);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/league-exp/v4/entries/RANKED_SOLO_5x5/CHALLENGER/I"
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn finds_summoner_by_name() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, SUMMONER_JSON),
+            MockResponse::new(404, ""),
+            MockResponse::new(500, ""),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let found = lapi.find_summoner_by_name("Vetro").await.unwrap();
+            assert_eq!(found.unwrap().name, "Vetro");
+            assert!(lapi
+                .find_summoner_by_name("Nobody")
+                .await
+                .unwrap()
+                .is_none());
+            assert!(matches!(
+                lapi.find_summoner_by_name("Broken").await,
+                Err(ClientError::InternalServerError { .. })
+            ));
+        })
+    }
+
+    #[test]
+    fn gets_third_party_code() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, r#""narwhal-code""#),
+            MockResponse::new(404, ""),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let code = lapi.get_third_party_code("with-code").await.unwrap();
+            assert_eq!(code.as_deref(), Some("narwhal-code"));
+            assert!(lapi
+                .get_third_party_code("without-code")
+                .await
+                .unwrap()
+                .is_none());
+        })
+    }
+
+    #[test]
+    fn gets_empty_clash_registrations() {
+        let server = MockServer::start(vec![MockResponse::new(200, "[]")]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let players = lapi.get_clash_players_by_summoner("test-id").await.unwrap();
+            assert!(players.is_empty());
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/clash/v1/players/by-summoner/test-id"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_clash_tournaments() {
+        let tournaments = r#"[{
+            "id": 2001,
+            "themeId": 1,
+            "nameKey": "bilgewater",
+            "nameKeySecondary": "day_1",
+            "schedule": [
+                {"id": 2101, "registrationTime": 1589999999000, "startTime": 1590003599000, "cancelled": false}
+            ]
+        }]"#;
+        let server = MockServer::start(vec![MockResponse::new(200, tournaments)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let tournaments = lapi.get_clash_tournaments().await.unwrap();
+            assert_eq!(tournaments[0].schedule[0].id, 2101);
+            assert_eq!(server.requests()[0].path, "/lol/clash/v1/tournaments");
+        })
+    }
+
+    #[test]
+    fn gets_challenge_player_data() {
+        let player = r#"{
+            "totalPoints": {"level": "GOLD", "current": 3100, "max": 23000, "percentile": 0.42},
+            "categoryPoints": {
+                "COLLECTION": {"level": "SILVER", "current": 400, "max": 2500, "percentile": 0.6}
+            },
+            "challenges": [
+                {"challengeId": 101000, "percentile": 0.3, "level": "GOLD", "value": 1200.0, "achievedTime": 1623801600000},
+                {"challengeId": 202303, "percentile": 1.0, "level": "NONE", "value": 0.0}
+            ],
+            "preferences": {}
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(200, player)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let info = lapi.get_challenge_player_data("test-puuid").await.unwrap();
+            assert_eq!(&info.total_points.level, "GOLD");
+            assert_eq!(info.category_points["COLLECTION"].current, 400);
+            assert_eq!(info.challenges.len(), 2);
+            assert_eq!(info.challenges[1].achieved_time, None);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/challenges/v1/player-data/test-puuid"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_tft_league_entries() {
+        let entries = r#"[
+            {
+                "leagueId": "test-league",
+                "queueType": "RANKED_TFT",
+                "tier": "GOLD",
+                "rank": "II",
+                "summonerId": "test-id",
+                "summonerName": "Vetro",
+                "leaguePoints": 42,
+                "wins": 10,
+                "losses": 20,
+                "veteran": false,
+                "inactive": false,
+                "freshBlood": true,
+                "hotStreak": false
+            },
+            {
+                "queueType": "RANKED_TFT_TURBO",
+                "ratedTier": "BLUE",
+                "ratedRating": 2100,
+                "summonerId": "test-id",
+                "summonerName": "Vetro",
+                "wins": 5,
+                "losses": 15
+            }
+        ]"#;
+        let server = MockServer::start(vec![MockResponse::new(200, entries)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let entries = lapi
+                .get_tft_league_entries_by_summoner("test-id")
                 .await
                 .unwrap();
-            assert!(challengers.len() > 0);
-            Ok(())
+            assert_eq!(entries[0].tier.as_deref(), Some("GOLD"));
+            assert_eq!(entries[1].rated_tier.as_deref(), Some("BLUE"));
+            assert_eq!(
+                server.requests()[0].path,
+                "/tft/league/v1/entries/by-summoner/test-id"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_tft_summoner_and_match_ids() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, SUMMONER_JSON),
+            MockResponse::new(200, r#"["NA1_1","NA1_2"]"#),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let summoner = lapi.get_tft_summoner_by_name("Vetro").await.unwrap();
+            let ids = lapi
+                .get_tft_match_ids_by_puuid(&summoner.puuid, None, Some(5))
+                .await
+                .unwrap();
+            assert_eq!(ids, vec!["NA1_1", "NA1_2"]);
+            let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+            assert_eq!(
+                paths,
+                vec![
+                    "/tft/summoner/v1/summoners/by-name/Vetro",
+                    "/tft/match/v1/matches/by-puuid/test-puuid/ids?start=0&count=5",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn streams_league_exp_pages() {
+        let entry = r#"{
+            "queueType": "RANKED_SOLO_5x5",
+            "summonerName": "Vetro",
+            "hotStreak": false,
+            "wins": 100,
+            "veteran": true,
+            "losses": 90,
+            "rank": "I",
+            "tier": "CHALLENGER",
+            "inactive": false,
+            "freshBlood": false,
+            "leagueId": "test-league",
+            "summonerId": "test-id",
+            "leaguePoints": 1000
+        }"#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, &format!("[{0},{0}]", entry)),
+            MockResponse::new(200, &format!("[{}]", entry)),
+            MockResponse::new(200, "[]"),
+        ]);
+        smol::run(async {
+            let mut lapi = mock_client(&server).build().unwrap();
+            let entries: Vec<_> = lapi
+                .league_exp_stream(RankedQueue::SOLO, RankedTier::CHALLENGER, Division::I)
+                .collect()
+                .await;
+            assert_eq!(entries.len(), 3);
+            assert!(entries.iter().all(Result::is_ok));
+            let requests = server.requests();
+            assert_eq!(requests.len(), 3);
+            assert!(requests[2].path.ends_with("?page=3"));
+        })
+    }
+
+    #[test]
+    fn streams_match_ids_pages() {
+        let page = |from: usize, len: usize| {
+            let ids: Vec<_> = (from..from + len).map(|i| format!("NA1_{}", i)).collect();
+            serde_json::to_string(&ids).unwrap()
+        };
+        let server = MockServer::start(vec![
+            MockResponse::new(200, &page(0, 100)),
+            MockResponse::new(200, &page(100, 100)),
+            MockResponse::new(200, &page(200, 50)),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let ids: Vec<_> = lapi
+                .match_ids_stream("test-puuid")
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(ids.len(), 250);
+            assert_eq!(&ids[249], "NA1_249");
+            let requests = server.requests();
+            assert_eq!(requests.len(), 3);
+            assert!(requests[2].path.ends_with("?start=200&count=100"));
+        })
+    }
+
+    #[test]
+    fn streams_league_exp_entries() {
+        let entry = r#"{
+            "queueType": "RANKED_SOLO_5x5",
+            "summonerName": "Vetro",
+            "hotStreak": false,
+            "wins": 100,
+            "veteran": true,
+            "losses": 90,
+            "rank": "I",
+            "tier": "CHALLENGER",
+            "inactive": false,
+            "freshBlood": false,
+            "leagueId": "test-league",
+            "summonerId": "test-id",
+            "leaguePoints": 1000
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            &format!("[{0},{0},{0}]", entry),
+        )]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let entries: Vec<_> = lapi
+                .league_exp_stream(RankedQueue::SOLO, RankedTier::CHALLENGER, Division::I)
+                .take(2)
+                .collect()
+                .await;
+            assert_eq!(entries.len(), 2);
+            assert!(entries.iter().all(Result::is_ok));
+            // Next page is not requested until the first one is consumed
+            assert_eq!(server.requests().len(), 1);
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_league_entries() {
+        smol::run(async {
+            let lapi = LeagueClient::new(Region::default()).unwrap();
+            let entries = lapi
+                .get_league_entries(
+                    RankedQueue::SOLO,
+                    RankedTier::DIAMOND,
+                    Division::IV,
+                    Some(1),
+                )
+                .await
+                .unwrap();
+            assert!(!entries.is_empty());
+            assert!(entries
+                .iter()
+                .all(|e| e.tier == "DIAMOND" && e.rank == "IV"));
+        })
+    }
+
+    #[test]
+    fn gets_league_entries_by_summoner() {
+        let entry = r#"{
+            "queueType": "RANKED_SOLO_5x5",
+            "summonerName": "Vetro",
+            "hotStreak": false,
+            "wins": 100,
+            "veteran": true,
+            "losses": 90,
+            "rank": "I",
+            "tier": "CHALLENGER",
+            "inactive": false,
+            "freshBlood": false,
+            "leagueId": "test-league",
+            "summonerId": "test-id",
+            "leaguePoints": 1000
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(200, &format!("[{}]", entry))]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let entries = lapi
+                .get_league_entries_by_summoner("test-id")
+                .await
+                .unwrap();
+            assert_eq!(&entries[0].queue_type, "RANKED_SOLO_5x5");
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/league/v4/entries/by-summoner/test-id"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_apex_leagues() {
+        let league = |tier: &str| {
+            format!(
+                r#"{{
+                    "leagueId": "test-league",
+                    "tier": "{}",
+                    "name": "Vetro's Champions",
+                    "queue": "RANKED_SOLO_5x5",
+                    "entries": [{{
+                        "summonerId": "test-id",
+                        "summonerName": "Vetro",
+                        "rank": "I",
+                        "leaguePoints": 1000,
+                        "wins": 100,
+                        "losses": 90,
+                        "hotStreak": false,
+                        "veteran": true,
+                        "inactive": false,
+                        "freshBlood": false
+                    }}]
+                }}"#,
+                tier
+            )
+        };
+        let server = MockServer::start(vec![
+            MockResponse::new(200, &league("CHALLENGER")),
+            MockResponse::new(200, &league("GRANDMASTER")),
+            MockResponse::new(200, &league("MASTER")),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let challenger = lapi.get_challenger_league(RankedQueue::SOLO).await.unwrap();
+            let grandmaster = lapi
+                .get_grandmaster_league(RankedQueue::SOLO)
+                .await
+                .unwrap();
+            let master = lapi.get_master_league(RankedQueue::SOLO).await.unwrap();
+            assert_eq!(&challenger.tier, "CHALLENGER");
+            assert_eq!(&grandmaster.tier, "GRANDMASTER");
+            assert_eq!(&master.tier, "MASTER");
+            assert_eq!(challenger.entries.len(), 1);
+            let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+            assert_eq!(
+                paths,
+                vec![
+                    "/lol/league/v4/challengerleagues/by-queue/RANKED_SOLO_5x5",
+                    "/lol/league/v4/grandmasterleagues/by-queue/RANKED_SOLO_5x5",
+                    "/lol/league/v4/masterleagues/by-queue/RANKED_SOLO_5x5",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_league_by_id() {
+        smol::run(async {
+            let lapi = LeagueClient::new(Region::default()).unwrap();
+            let challenger = lapi.get_challenger_league(RankedQueue::SOLO).await.unwrap();
+            let league = lapi.get_league_by_id(&challenger.league_id).await.unwrap();
+            assert_eq!(league.league_id, challenger.league_id);
+            assert_eq!(&league.tier, "CHALLENGER");
+        })
+    }
+
+    #[test]
+    fn gets_match_ids_and_match() {
+        let game = r#"{
+            "metadata": {"dataVersion": "2", "matchId": "NA1_1", "participants": ["test-puuid"]},
+            "info": {
+                "gameCreation": 1589999999000,
+                "gameDuration": 1800,
+                "gameId": 1,
+                "gameMode": "CLASSIC",
+                "gameType": "MATCHED_GAME",
+                "gameVersion": "10.10.320.2500",
+                "mapId": 11,
+                "platformId": "NA1",
+                "queueId": 420,
+                "participants": [{
+                    "puuid": "test-puuid",
+                    "summonerId": "test-id",
+                    "summonerName": "Vetro",
+                    "participantId": 1,
+                    "teamId": 100,
+                    "championId": 64,
+                    "championName": "LeeSin",
+                    "champLevel": 18,
+                    "summoner1Id": 4,
+                    "summoner2Id": 11,
+                    "kills": 10,
+                    "deaths": 2,
+                    "assists": 7,
+                    "goldEarned": 15000,
+                    "totalMinionsKilled": 60,
+                    "item0": 3071,
+                    "item1": 0,
+                    "item2": 0,
+                    "item3": 0,
+                    "item4": 0,
+                    "item5": 0,
+                    "item6": 3364,
+                    "teamPosition": "JUNGLE",
+                    "win": true
+                }],
+                "teams": [{"teamId": 100, "win": true, "bans": [{"championId": 157, "pickTurn": 1}]}]
+            }
+        }"#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, r#"["NA1_1"]"#),
+            MockResponse::new(200, game),
+        ]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let ids = lapi
+                .get_match_ids_by_puuid("test-puuid", None, Some(5))
+                .await
+                .unwrap();
+            assert_eq!(ids, vec!["NA1_1"]);
+            let game = lapi.get_match(&ids[0]).await.unwrap();
+            assert_eq!(game.metadata.match_id, ids[0]);
+            assert_eq!(game.info.participants[0].champion_id, 64);
+            assert_eq!(server.requests()[1].path, "/lol/match/v5/matches/NA1_1");
+        })
+    }
+
+    #[test]
+    fn enriched_match_requires_ddragon() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"{
+                "metadata": {"dataVersion": "2", "matchId": "NA1_1", "participants": []},
+                "info": {
+                    "gameCreation": 0, "gameDuration": 0, "gameId": 1, "gameMode": "CLASSIC",
+                    "gameType": "MATCHED_GAME", "gameVersion": "13.10.509.8402", "mapId": 11,
+                    "platformId": "NA1", "queueId": 420, "participants": [], "teams": []
+                }
+            }"#,
+        )]);
+        smol::run(async {
+            let mut lapi = mock_client(&server).build().unwrap();
+            let err = lapi.get_match_enriched("NA1_1").await.unwrap_err();
+            assert!(matches!(err, ClientError::DDragonNotAttached));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_enriched_match() {
+        smol::run(async {
+            let mut lapi = LeagueClient::new(Region::NA)
+                .unwrap()
+                .with_ddragon(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            let ids = lapi
+                .get_match_ids_by_puuid(&summoner.puuid, None, Some(1))
+                .await
+                .unwrap();
+            let game = lapi.get_match_enriched(&ids[0]).await.unwrap();
+            assert_eq!(game.participants.len(), game.data.info.participants.len());
+            for (enriched, raw) in game.participants.iter().zip(&game.data.info.participants) {
+                assert_eq!(enriched.champion.id, raw.champion_name);
+            }
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn gets_matches() {
+        smol::run(async {
+            let lapi = LeagueClient::new(Region::NA).unwrap();
+            let summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            let ids = lapi
+                .get_match_ids_by_puuid(&summoner.puuid, None, Some(5))
+                .await
+                .unwrap();
+            let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+            let matches = lapi.get_matches(&ids).await;
+            assert_eq!(matches.len(), 5);
+            for (id, game) in ids.iter().zip(matches) {
+                assert_eq!(game.unwrap().metadata.match_id, *id);
+            }
+        })
+    }
+
+    #[test]
+    fn gets_match_timeline() {
+        let timeline = r#"{
+            "metadata": {"dataVersion": "2", "matchId": "NA1_1", "participants": ["test-puuid"]},
+            "info": {
+                "frameInterval": 60000,
+                "frames": [{
+                    "timestamp": 60000,
+                    "events": [
+                        {"type": "LEVEL_UP", "timestamp": 1000, "participantId": 1, "level": 2},
+                        {"type": "SOMETHING_NEW", "timestamp": 2000}
+                    ],
+                    "participantFrames": {
+                        "1": {
+                            "participantId": 1,
+                            "level": 2,
+                            "xp": 300,
+                            "currentGold": 500,
+                            "totalGold": 500,
+                            "minionsKilled": 0,
+                            "jungleMinionsKilled": 4,
+                            "position": {"x": 1000, "y": 2000}
+                        }
+                    }
+                }]
+            }
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(200, timeline)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let timeline = lapi.get_match_timeline("NA1_1").await.unwrap();
+            assert_eq!(timeline.info.frames[0].events.len(), 2);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/match/v5/matches/NA1_1/timeline"
+            );
+        })
+    }
+
+    #[test]
+    fn returns_not_found_for_summoner_not_in_game() {
+        let server = MockServer::start(vec![MockResponse::new(404, "")]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let err = lapi.get_active_game("test-id").await.unwrap_err();
+            assert!(matches!(err, ClientError::DataNotFound { .. }));
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/spectator/v4/active-games/by-summoner/test-id"
+            );
+        })
+    }
+
+    #[test]
+    fn enriching_live_game_requires_ddragon() {
+        let game: CurrentGameInfo = serde_json::from_str(
+            r#"{
+                "gameId": 1,
+                "gameType": "MATCHED_GAME",
+                "gameStartTime": 0,
+                "mapId": 11,
+                "gameLength": 0,
+                "platformId": "NA1",
+                "gameMode": "CLASSIC",
+                "bannedChampions": [],
+                "participants": []
+            }"#,
+        )
+        .unwrap();
+        smol::run(async {
+            let mut lapi = LeagueClientBuilder::new(Region::NA)
+                .api_key(TEST_KEY)
+                .build()
+                .unwrap();
+            let err = lapi.enrich_live_game(&game).await.unwrap_err();
+            assert!(matches!(err, ClientError::DDragonNotAttached));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits Riot API, needs RIOT_API_KEY"]
+    fn enriches_live_game_with_partial_failures() {
+        smol::run(async {
+            let mut lapi = LeagueClient::new(Region::NA)
+                .unwrap()
+                .with_ddragon(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let summoner = lapi.get_summoner_by_name("Santorin").await.unwrap();
+            let participant = |summoner_id: &str| {
+                format!(
+                    r#"{{
+                        "championId": 64,
+                        "profileIconId": 1,
+                        "bot": false,
+                        "teamId": 100,
+                        "summonerName": "",
+                        "summonerId": "{}",
+                        "spell1Id": 4,
+                        "spell2Id": 11
+                    }}"#,
+                    summoner_id
+                )
+            };
+            let game: CurrentGameInfo = serde_json::from_str(&format!(
+                r#"{{
+                    "gameId": 1,
+                    "gameType": "MATCHED_GAME",
+                    "gameStartTime": 0,
+                    "mapId": 11,
+                    "gameLength": 0,
+                    "platformId": "NA1",
+                    "gameMode": "CLASSIC",
+                    "bannedChampions": [],
+                    "participants": [{}, {}]
+                }}"#,
+                participant(&summoner.id),
+                participant("not-a-summoner-id")
+            ))
+            .unwrap();
+            let enriched = lapi.enrich_live_game(&game).await.unwrap();
+            assert_eq!(enriched.len(), 2);
+            assert_eq!(&enriched[0].champion.as_ref().unwrap().id, "LeeSin");
+            assert!(enriched[0].leagues.is_ok());
+            assert!(enriched[1].leagues.is_err());
+        })
+    }
+
+    #[test]
+    fn gets_featured_games() {
+        let featured = r#"{
+            "gameList": [{
+                "gameId": 1,
+                "gameType": "MATCHED_GAME",
+                "gameStartTime": 1589999999000,
+                "mapId": 11,
+                "gameLength": 600,
+                "platformId": "NA1",
+                "gameMode": "CLASSIC",
+                "bannedChampions": [{"pickTurn": 1, "championId": 157, "teamId": 100}],
+                "gameQueueConfigId": 420,
+                "participants": [{
+                    "championId": 64,
+                    "profileIconId": 1,
+                    "bot": false,
+                    "teamId": 100,
+                    "summonerName": "Vetro",
+                    "spell1Id": 4,
+                    "spell2Id": 11
+                }]
+            }],
+            "clientRefreshInterval": 300
+        }"#;
+        let server = MockServer::start(vec![MockResponse::new(200, featured)]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let featured = lapi.get_featured_games().await.unwrap();
+            assert_eq!(featured.client_refresh_interval, 300);
+            assert_eq!(featured.game_list[0].participants[0].champion_id, 64);
+            assert_eq!(
+                server.requests()[0].path,
+                "/lol/spectator/v4/featured-games"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_account_by_riot_id() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"{"puuid":"test-puuid","gameName":"Hide on bush","tagLine":"KR1"}"#,
+        )]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let account = lapi
+                .get_account_by_riot_id("Hide on bush", "KR1")
+                .await
+                .unwrap();
+            assert_eq!(account.game_name.as_deref(), Some("Hide on bush"));
+            assert_eq!(
+                server.requests()[0].path,
+                "/riot/account/v1/accounts/by-riot-id/Hide%20on%20bush/KR1"
+            );
+        })
+    }
+
+    #[test]
+    fn gets_account_by_puuid() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"{"puuid":"test-puuid","gameName":"Hide on bush","tagLine":"KR1"}"#,
+        )]);
+        smol::run(async {
+            let lapi = mock_client(&server).build().unwrap();
+            let account = lapi.get_account_by_puuid("test-puuid").await.unwrap();
+            assert_eq!(account.tag_line.as_deref(), Some("KR1"));
+            assert_eq!(
+                server.requests()[0].path,
+                "/riot/account/v1/accounts/by-puuid/test-puuid"
+            );
         })
     }
 }
@@ -0,0 +1,394 @@
+//! Cache of raw api responses.
+//!
+//! Responses are stored as raw json keyed by request url, so one [`Cache`] can be shared
+//! between [`LeagueClient`] and [`DDragonClient`].
+//!
+//! Any storage implementing [`CacheBackend`] can be used as a cache, e.g. Redis to share
+//! responses between processes. By default in-memory [`ResponseCache`] is used.
+//!
+//! [`Cache`]: type.Cache.html
+//! [`CacheBackend`]: trait.CacheBackend.html
+//! [`ResponseCache`]: struct.ResponseCache.html
+//! [`LeagueClient`]: ../api/struct.LeagueClient.html
+//! [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
+use async_trait::async_trait;
+use hyper::Uri;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Cache of raw responses keyed by request url, shared between clients
+pub type Cache = Arc<dyn CacheBackend>;
+
+/// Key of the response to `url`, shared by [`LeagueClient`] and [`DDragonClient`]
+/// so their entries never end up under differently formatted keys.
+///
+/// Scheme and host are case insensitive, so they are lowercased.
+///
+/// [`LeagueClient`]: ../api/struct.LeagueClient.html
+/// [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
+pub(crate) fn cache_key(url: &Uri) -> String {
+    let origin = match (url.scheme_str(), url.authority()) {
+        (Some(scheme), Some(authority)) => {
+            format!("{}://{}", scheme, authority).to_ascii_lowercase()
+        }
+        _ => String::new(),
+    };
+    let path = url.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    format!("{}{}", origin, path)
+}
+
+/// Storage of raw responses keyed by request url.
+#[async_trait]
+pub trait CacheBackend: Debug + Send + Sync {
+    /// Gets not expired raw response stored under the key
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores raw response under the key, it should not be served after `ttl` passes
+    async fn insert(&self, key: &str, value: String, ttl: Option<Duration>);
+
+    /// Same as [`insert`], but also keeps `ETag` of the response for revalidation
+    ///
+    /// [`insert`]: #tymethod.insert
+    async fn insert_with_etag(
+        &self,
+        key: &str,
+        value: String,
+        ttl: Option<Duration>,
+        _etag: Option<String>,
+    ) {
+        self.insert(key, value, ttl).await
+    }
+
+    /// Gets raw response stored under the key together with its `ETag`, even if it has expired,
+    /// so it can be revalidated with a conditional request. Backends not storing `ETag`s return `None`
+    async fn get_stale_with_etag(&self, _key: &str) -> Option<(String, String)> {
+        None
+    }
+
+    /// Removes all stored responses
+    async fn clear(&self);
+
+    /// Usage statistics, backends that do not track them report zeros
+    fn stats(&self) -> CacheStats {
+        CacheStats::default()
+    }
+}
+
+/// In-memory backend. `parking_lot` mutex is not poisoned by a panic of another thread
+/// holding the lock, so the cache stays usable in long running services.
+#[async_trait]
+impl CacheBackend for Mutex<ResponseCache> {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.lock().get(key).map(|entry| entry.payload().to_owned())
+    }
+
+    async fn insert(&self, key: &str, value: String, ttl: Option<Duration>) {
+        self.lock()
+            .insert(key.to_owned(), CacheEntry::new(value, ttl))
+    }
+
+    async fn insert_with_etag(
+        &self,
+        key: &str,
+        value: String,
+        ttl: Option<Duration>,
+        etag: Option<String>,
+    ) {
+        let mut entry = CacheEntry::new(value, ttl);
+        if let Some(etag) = etag {
+            entry = entry.with_etag(etag);
+        }
+        self.lock().insert(key.to_owned(), entry)
+    }
+
+    async fn get_stale_with_etag(&self, key: &str) -> Option<(String, String)> {
+        let cache = self.lock();
+        let entry = cache.get_stale(key)?;
+        Some((entry.payload().to_owned(), entry.etag()?.to_owned()))
+    }
+
+    async fn clear(&self) {
+        self.lock().clear()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+}
+
+/// Map of request urls to responses, optionally bounded by number of entries.
+///
+/// When the bound is exceeded, least recently used entries are evicted.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    entries: HashMap<String, (CacheEntry, u64)>,
+    /// Urls ordered by the last time they were used
+    usage: BTreeMap<u64, String>,
+    tick: u64,
+    max_entries: Option<usize>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Statistics of [`ResponseCache`] usage.
+///
+/// [`ResponseCache`]: struct.ResponseCache.html
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of requests served from cache
+    pub hits: u64,
+    /// Number of requests that had to be fetched
+    pub misses: u64,
+    /// Number of entries currently in cache
+    pub entries: usize,
+}
+
+/// Response together with whether it was served from cache.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cached<T> {
+    pub value: T,
+    /// `true` if the response was found in cache and no request was made
+    pub from_cache: bool,
+}
+
+impl<T> Cached<T> {
+    /// Drops provenance, returning only the response
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl ResponseCache {
+    /// Creates unbounded cache
+    pub fn new() -> Self {
+        ResponseCache::default()
+    }
+
+    /// Creates cache that keeps at most `max_entries` entries
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        ResponseCache {
+            max_entries: Some(max_entries),
+            ..ResponseCache::default()
+        }
+    }
+
+    /// Gets not expired entry of the url, marking it as recently used
+    pub fn get(&mut self, url: &str) -> Option<&CacheEntry> {
+        let (entry, used) = match self.entries.get_mut(url) {
+            Some((entry, used)) if !entry.is_expired() => (entry, used),
+            _ => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        self.hits += 1;
+        self.usage.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.usage.insert(self.tick, url.to_owned());
+        Some(entry)
+    }
+
+    /// Gets entry of the url even if it has expired, without marking it as used or counting a hit
+    pub fn get_stale(&self, url: &str) -> Option<&CacheEntry> {
+        self.entries.get(url).map(|(entry, _)| entry)
+    }
+
+    /// Inserts entry of the url, evicting least recently used entries if cache is full
+    pub fn insert(&mut self, url: String, entry: CacheEntry) {
+        self.tick += 1;
+        self.usage.insert(self.tick, url.clone());
+        if let Some((_, used)) = self.entries.insert(url, (entry, self.tick)) {
+            self.usage.remove(&used);
+        }
+        if let Some(max_entries) = self.max_entries {
+            while self.entries.len() > max_entries {
+                let oldest = *self.usage.keys().next().unwrap();
+                let url = self.usage.remove(&oldest).unwrap();
+                self.entries.remove(&url);
+            }
+        }
+    }
+
+    /// Urls of all cached entries
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Number of cached entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no cached entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all entries, keeping hit and miss counters
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.usage.clear();
+    }
+
+    /// Hit and miss counters along with current number of entries
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+}
+
+/// Raw response stored in [`Cache`] together with its expiration time.
+///
+/// [`Cache`]: type.Cache.html
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    payload: String,
+    expires_at: Option<Instant>,
+    etag: Option<String>,
+}
+
+impl CacheEntry {
+    /// Creates entry that expires after `ttl`, or never if `ttl` is `None`
+    pub fn new(payload: String, ttl: Option<Duration>) -> Self {
+        CacheEntry {
+            payload,
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+            etag: None,
+        }
+    }
+
+    /// Attaches `ETag` the response was served with
+    pub fn with_etag(mut self, etag: String) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// `ETag` of the response, if the server sent one
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Raw json of the response
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+
+    /// Whether entry is too old to be served
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| Instant::now() >= expires_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_key, Cache, CacheEntry, ResponseCache};
+    use hyper::Uri;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn entry_expires_after_ttl() {
+        let entry = CacheEntry::new("{}".to_owned(), Some(Duration::from_millis(0)));
+        assert!(entry.is_expired());
+        let entry = CacheEntry::new("{}".to_owned(), None);
+        assert!(!entry.is_expired());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = ResponseCache::with_max_entries(2);
+        cache.insert("first".to_owned(), CacheEntry::new("1".to_owned(), None));
+        cache.insert("second".to_owned(), CacheEntry::new("2".to_owned(), None));
+        assert!(cache.get("first").is_some());
+        cache.insert("third".to_owned(), CacheEntry::new("3".to_owned(), None));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("second").is_none());
+        assert!(cache.get("first").is_some());
+        assert!(cache.get("third").is_some());
+    }
+
+    #[test]
+    fn keys_lapi_and_ddragon_urls_alike() {
+        let lapi: Uri = "HTTPS://NA1.api.riotgames.com/lol/summoner/v4/summoners/by-name/Vetro"
+            .parse()
+            .unwrap();
+        let ddragon: Uri =
+            "https://ddragon.leagueoflegends.com/cdn/10.10.3216176/data/en_US/champion.json"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            cache_key(&lapi),
+            "https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-name/Vetro"
+        );
+        assert_eq!(cache_key(&ddragon), ddragon.to_string());
+
+        let cache: Cache = Arc::new(Mutex::new(ResponseCache::new()));
+        smol::run(async {
+            cache
+                .insert(&cache_key(&lapi), "lapi".to_owned(), None)
+                .await;
+            cache
+                .insert(&cache_key(&ddragon), "ddragon".to_owned(), None)
+                .await;
+            assert_eq!(cache.get(&cache_key(&lapi)).await.unwrap(), "lapi");
+            assert_eq!(cache.get(&cache_key(&ddragon)).await.unwrap(), "ddragon");
+        });
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn in_memory_backend_serves_stored_responses() {
+        let cache: Cache = Arc::new(Mutex::new(ResponseCache::new()));
+        smol::run(async {
+            assert!(cache.get("url").await.is_none());
+            cache.insert("url", "{}".to_owned(), None).await;
+            assert_eq!(cache.get("url").await.unwrap(), "{}");
+            cache.clear().await;
+            assert!(cache.get("url").await.is_none());
+        });
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 0));
+    }
+
+    #[test]
+    fn keeps_etag_of_expired_entries() {
+        let cache: Cache = Arc::new(Mutex::new(ResponseCache::new()));
+        smol::run(async {
+            let ttl = Some(Duration::from_millis(0));
+            cache.insert("plain", "{}".to_owned(), ttl).await;
+            cache
+                .insert_with_etag("tagged", "[]".to_owned(), ttl, Some("\"v1\"".to_owned()))
+                .await;
+            assert!(cache.get("tagged").await.is_none());
+            assert!(cache.get_stale_with_etag("plain").await.is_none());
+            assert_eq!(
+                cache.get_stale_with_etag("tagged").await,
+                Some(("[]".to_owned(), "\"v1\"".to_owned()))
+            );
+        });
+    }
+
+    #[test]
+    fn stays_usable_after_panic_while_locked() {
+        let cache = Arc::new(Mutex::new(ResponseCache::new()));
+        let cloned = cache.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = cloned.lock();
+            panic!("panic while holding cache lock");
+        })
+        .join();
+        cache
+            .lock()
+            .insert("url".to_owned(), CacheEntry::new("{}".to_owned(), None));
+        assert_eq!(cache.lock().len(), 1);
+    }
+}
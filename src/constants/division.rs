@@ -1,8 +1,10 @@
+use crate::error::{ClientError, UnknownDivision};
 use std::convert::AsRef;
 use std::fmt::{self, Debug};
+use std::str::FromStr;
 use Inner::*;
 
-#[derive(Debug, Clone, Eq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Division(Inner);
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -53,13 +55,6 @@ impl<'a> PartialEq<Division> for &'a Division {
     }
 }
 
-impl PartialEq<Division> for Division {
-    #[inline]
-    fn eq(&self, other: &Division) -> bool {
-        self == other
-    }
-}
-
 impl PartialEq<str> for Division {
     #[inline]
     fn eq(&self, other: &str) -> bool {
@@ -87,6 +82,21 @@ impl fmt::Display for Division {
     }
 }
 
+impl FromStr for Division {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Division, ClientError> {
+        match s.to_ascii_uppercase().as_str() {
+            "I" => Ok(Division::I),
+            "II" => Ok(Division::II),
+            "III" => Ok(Division::III),
+            "IV" => Ok(Division::IV),
+            _ => UnknownDivision { input: s }.fail(),
+        }
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::Division;
 
@@ -95,4 +105,13 @@ mod tests {
         let division = Division::III;
         assert_eq!(division.as_str(), "III")
     }
+
+    #[test]
+    fn division_round_trips_through_string() {
+        let divisions = [Division::I, Division::II, Division::III, Division::IV];
+        for division in divisions.iter() {
+            assert_eq!(&division.to_string().parse::<Division>().unwrap(), division);
+        }
+        assert!("V".parse::<Division>().is_err());
+    }
 }
@@ -1,7 +1,10 @@
 use self::Inner::*;
 
+use crate::error::{ClientError, UnknownLanguageCode};
+use snafu::OptionExt;
 use std::convert::AsRef;
 use std::fmt;
+use std::str::FromStr;
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct LanguageCode(Inner);
@@ -96,6 +99,41 @@ impl LanguageCode {
     /// Greece language code
     pub const TAIWAN: LanguageCode = LanguageCode(Taiwan);
 
+    /// All supported language codes
+    pub fn all() -> &'static [LanguageCode] {
+        const ALL: &[LanguageCode] = &[
+            LanguageCode::CZECH_REPUBLIC,
+            LanguageCode::GREECE,
+            LanguageCode::POLAND,
+            LanguageCode::ROMANIA,
+            LanguageCode::HUNGARY,
+            LanguageCode::UNITED_KINGDOM,
+            LanguageCode::GERMANY,
+            LanguageCode::SPAIN,
+            LanguageCode::ITALY,
+            LanguageCode::FRANCE,
+            LanguageCode::JAPAN,
+            LanguageCode::KOREA,
+            LanguageCode::MEXICO,
+            LanguageCode::ARGENTINA,
+            LanguageCode::BRAZIL,
+            LanguageCode::UNITED_STATES,
+            LanguageCode::AUSTRALIA,
+            LanguageCode::RUSSIA,
+            LanguageCode::TURKEY,
+            LanguageCode::MALAYSIA,
+            LanguageCode::PHILIPINNES,
+            LanguageCode::SINGAPORE,
+            LanguageCode::THAILAND,
+            LanguageCode::VIETNAM,
+            LanguageCode::INDONESIA,
+            LanguageCode::MALAYSIA_CHINESE,
+            LanguageCode::CHINA,
+            LanguageCode::TAIWAN,
+        ];
+        ALL
+    }
+
     #[inline]
     pub fn as_str(&self) -> &str {
         match self.0 {
@@ -122,7 +160,7 @@ impl LanguageCode {
             RepublicOfThePhilipinnes => "en_PH",
             Singapore => "en_SG",
             Thailand => "th_TH",
-            Vietnam => "vn_VN",
+            Vietnam => "vi_VN",
             Indonesia => "id_ID",
             MalaysiaChinese => "zh_MY",
             China => "zh_CN",
@@ -173,6 +211,20 @@ impl PartialEq<LanguageCode> for str {
     }
 }
 
+impl FromStr for LanguageCode {
+    type Err = ClientError;
+
+    /// Parses DDragon locale (`en_US`), also accepting `en-US` and any case.
+    fn from_str(s: &str) -> Result<LanguageCode, ClientError> {
+        let normalized = s.replace('-', "_");
+        LanguageCode::all()
+            .iter()
+            .find(|code| code.as_str().eq_ignore_ascii_case(&normalized))
+            .cloned()
+            .context(UnknownLanguageCode { input: s })
+    }
+}
+
 impl fmt::Debug for LanguageCode {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.write_str(self.as_ref())
@@ -188,9 +240,29 @@ impl fmt::Display for LanguageCode {
 #[cfg(test)]
 mod tests {
     use super::LanguageCode;
+    use crate::error::ClientError;
 
     #[test]
     fn lang_code_returns_correct_lang_string() {
         assert_eq!(LanguageCode::TURKEY, "tr_TR")
     }
+
+    #[test]
+    fn lang_code_parses_from_str() {
+        assert_eq!(
+            "ko_KR".parse::<LanguageCode>().unwrap(),
+            LanguageCode::KOREA
+        );
+        assert_eq!(
+            "en-us".parse::<LanguageCode>().unwrap(),
+            LanguageCode::UNITED_STATES
+        );
+        assert!(matches!(
+            "xx_XX".parse::<LanguageCode>(),
+            Err(ClientError::UnknownLanguageCode { .. })
+        ));
+        for code in LanguageCode::all() {
+            assert_eq!(&code.as_str().parse::<LanguageCode>().unwrap(), code);
+        }
+    }
 }
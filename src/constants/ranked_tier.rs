@@ -1,5 +1,7 @@
+use crate::error::{ClientError, UnknownRankedTier};
 use std::convert::AsRef;
 use std::fmt;
+use std::str::FromStr;
 use Inner::*;
 
 #[derive(Clone, PartialEq, Eq)]
@@ -105,3 +107,47 @@ impl fmt::Display for RankedTier {
         fmt.write_str(self.as_ref())
     }
 }
+
+impl FromStr for RankedTier {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<RankedTier, ClientError> {
+        match s.to_ascii_uppercase().as_str() {
+            "IRON" => Ok(RankedTier::IRON),
+            "BRONZE" => Ok(RankedTier::BRONZE),
+            "SILVER" => Ok(RankedTier::SILVER),
+            "GOLD" => Ok(RankedTier::GOLD),
+            "PLATINUM" => Ok(RankedTier::PLATINUM),
+            "DIAMOND" => Ok(RankedTier::DIAMOND),
+            "MASTER" => Ok(RankedTier::MASTER),
+            "GRANDMASTER" => Ok(RankedTier::GRANDMASTER),
+            "CHALLENGER" => Ok(RankedTier::CHALLENGER),
+            _ => UnknownRankedTier { input: s }.fail(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RankedTier;
+
+    #[test]
+    fn ranked_tier_round_trips_through_string() {
+        let tiers = [
+            RankedTier::IRON,
+            RankedTier::BRONZE,
+            RankedTier::SILVER,
+            RankedTier::GOLD,
+            RankedTier::PLATINUM,
+            RankedTier::DIAMOND,
+            RankedTier::MASTER,
+            RankedTier::GRANDMASTER,
+            RankedTier::CHALLENGER,
+        ];
+        for tier in tiers.iter() {
+            assert_eq!(&tier.to_string().parse::<RankedTier>().unwrap(), tier);
+        }
+        assert_eq!(RankedTier::CHALLENGER.to_string(), "CHALLENGER");
+        assert!("WOOD".parse::<RankedTier>().is_err());
+    }
+}
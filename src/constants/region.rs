@@ -1,6 +1,8 @@
 use self::Inner::*;
 
+use crate::error::{ClientError, UnknownRegion};
 use std::convert::AsRef;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Region(Inner);
@@ -47,6 +49,25 @@ impl Region {
     /// Public Beta Environment
     pub const PBE: Region = Region(PBE);
 
+    /// All platforms, including PBE
+    pub fn all() -> &'static [Region] {
+        const ALL: &[Region] = &[
+            Region::BR,
+            Region::EUNE,
+            Region::EUW,
+            Region::JP,
+            Region::KR,
+            Region::LAN,
+            Region::LAS,
+            Region::NA,
+            Region::OCE,
+            Region::TR,
+            Region::RU,
+            Region::PBE,
+        ];
+        ALL
+    }
+
     /// String representation of Region
     #[inline]
     pub fn as_str(&self) -> &str {
@@ -84,6 +105,25 @@ impl Region {
             PBE => "PBE1",
         }
     }
+
+    /// Regional routing value used by regional endpoints (e.g. match-v5)
+    #[inline]
+    pub fn as_regional_str(&self) -> &str {
+        match self.0 {
+            BR | LAN | LAS | NA | OCE | PBE => "americas",
+            JP | KR => "asia",
+            EUNE | EUW | TR | RU => "europe",
+        }
+    }
+
+    /// Regional routing value used by account-v1, OCE accounts are looked up on `asia`
+    #[inline]
+    pub fn as_account_regional_str(&self) -> &str {
+        match self.0 {
+            OCE => "asia",
+            _ => self.as_regional_str(),
+        }
+    }
 }
 
 impl AsRef<str> for Region {
@@ -93,7 +133,31 @@ impl AsRef<str> for Region {
     }
 }
 
+impl FromStr for Region {
+    type Err = ClientError;
+
+    /// Parses both friendly (`EUW`) and platform (`EUW1`) spellings, ignoring case.
+    fn from_str(s: &str) -> Result<Region, ClientError> {
+        match s.to_ascii_uppercase().as_str() {
+            "BR" | "BR1" => Ok(Region::BR),
+            "EUNE" | "EUN1" => Ok(Region::EUNE),
+            "EUW" | "EUW1" => Ok(Region::EUW),
+            "JP" | "JP1" => Ok(Region::JP),
+            "KR" => Ok(Region::KR),
+            "LAN" | "LA1" => Ok(Region::LAN),
+            "LAS" | "LA2" => Ok(Region::LAS),
+            "NA" | "NA1" => Ok(Region::NA),
+            "OCE" | "OC1" => Ok(Region::OCE),
+            "TR" | "TR1" => Ok(Region::TR),
+            "RU" => Ok(Region::RU),
+            "PBE" | "PBE1" => Ok(Region::PBE),
+            _ => UnknownRegion { input: s }.fail(),
+        }
+    }
+}
+
 impl Default for Region {
+    /// Defaults to North America
     #[inline]
     fn default() -> Region {
         Region::NA
@@ -103,9 +167,60 @@ impl Default for Region {
 #[cfg(test)]
 mod tests {
     use super::Region;
+    use crate::error::ClientError;
 
     #[test]
     fn region_gets_correct_platform_string() {
         assert_eq!(Region::NA.as_platform_str(), "NA1")
     }
+
+    #[test]
+    fn region_gets_correct_regional_string() {
+        let routes = [
+            (Region::BR, "americas"),
+            (Region::LAN, "americas"),
+            (Region::LAS, "americas"),
+            (Region::NA, "americas"),
+            (Region::OCE, "americas"),
+            (Region::PBE, "americas"),
+            (Region::JP, "asia"),
+            (Region::KR, "asia"),
+            (Region::EUNE, "europe"),
+            (Region::EUW, "europe"),
+            (Region::TR, "europe"),
+            (Region::RU, "europe"),
+        ];
+        for (region, route) in routes.iter() {
+            assert_eq!(region.as_regional_str(), *route, "{:?}", region);
+        }
+    }
+
+    #[test]
+    fn account_routing_sends_oce_to_asia() {
+        assert_eq!(Region::OCE.as_account_regional_str(), "asia");
+        assert_eq!(Region::NA.as_account_regional_str(), "americas");
+        assert_eq!(Region::EUW.as_account_regional_str(), "europe");
+    }
+
+    #[test]
+    fn all_lists_every_platform() {
+        let all = Region::all();
+        assert_eq!(all.len(), 12);
+        assert!(all.contains(&Region::default()));
+        for region in all {
+            assert!(!region.as_platform_str().is_empty(), "{:?}", region);
+        }
+    }
+
+    #[test]
+    fn region_parses_from_str() {
+        assert_eq!("NA".parse::<Region>().unwrap(), Region::NA);
+        assert_eq!("euw1".parse::<Region>().unwrap(), Region::EUW);
+        assert_eq!("La2".parse::<Region>().unwrap(), Region::LAS);
+        assert_eq!("kr".parse::<Region>().unwrap(), Region::KR);
+        assert!(matches!(
+            "Narnia".parse::<Region>(),
+            Err(ClientError::UnknownRegion { .. })
+        ));
+    }
 }
@@ -1,12 +1,30 @@
-use crate::constants::LanguageCode;
-use crate::dto::ddragon::{AllChampions, ChampionExtended, ChampionFullData};
-use crate::error::{ClientError, HyperError};
-use crate::types::{Cache, Client};
-use crate::utils::{construct_hyper_client, get_latest_ddragon_version, CachedClient};
-use log::debug;
+//! Async wrapper for DDragon static data API.
+//!
+//! [`DDragonClient`] runs on the same hyper client and runtime compat layer as [`LeagueClient`],
+//! so every request is non-blocking and can be awaited alongside League API calls.
+//!
+//! [`DDragonClient`]: struct.DDragonClient.html
+//! [`LeagueClient`]: ../api/struct.LeagueClient.html
+use crate::cache::{cache_key, Cache, ResponseCache};
+use crate::constants::{LanguageCode, Region};
+use crate::dto::ddragon::{
+    AllChampions, AllItems, AllSummonerSpells, ChampionExtended, ChampionFullData, Realm, RuneTree,
+    SummonerSpellData,
+};
+use crate::error::{
+    ChampionNotFound, ClientError, Deserialization, FromUTF8Error, HyperError,
+    SummonerSpellNotFound, UnknownGameVersion,
+};
+use crate::types::Client;
+use crate::utils::{
+    construct_hyper_client, get_ddragon_versions, get_latest_ddragon_version, parse_url,
+    CachedClient,
+};
+use log::{debug, warn};
 
 use futures::prelude::*;
-use hyper::{Body, Request, Uri};
+use hyper::header::{ETAG, IF_NONE_MATCH};
+use hyper::{Body, Request, StatusCode, Uri};
 
 use std::collections::HashMap;
 
@@ -15,98 +33,492 @@ use async_trait::async_trait;
 use hyper::header::HeaderValue;
 use parking_lot::Mutex;
 use serde::de::DeserializeOwned;
-use snafu::ResultExt;
+use snafu::{ensure, OptionExt, ResultExt};
 use std::fmt::Debug;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+/// Async client for DDragon static data.
+/// Instances of `DDragonClient` can be created using [`new`] with a [`LanguageCode`] parameter,
+/// or embedded into [`LeagueClient`] using [`with_ddragon`].
+///
+/// [`new`]: #method.new
+/// [`LanguageCode`]: ../constants/lang_code/struct.LanguageCode.html
+/// [`LeagueClient`]: ../api/struct.LeagueClient.html
+/// [`with_ddragon`]: ../api/struct.LeagueClient.html#method.with_ddragon
 #[derive(Debug)]
 pub struct DDragonClient {
     client: Client,
     cache: Cache,
+    language: LanguageCode,
     version: String,
+    host: String,
     base_url: String,
+    cache_dir: Option<PathBuf>,
+    revalidate_after: Option<Duration>,
 }
 
 impl DDragonClient {
+    /// Creates new client with its own hyper client and cache, fetching the latest DDragon version.
     pub async fn new(language: LanguageCode) -> Result<DDragonClient, ClientError> {
-        let client = construct_hyper_client();
-        let cache: Cache = Arc::new(Mutex::new(HashMap::new()));
-        let version = get_latest_ddragon_version(client.clone()).await?;
-        let base_url = format!(
-            "https://ddragon.leagueoflegends.com/cdn/{}/data/{}",
-            version, &language
-        );
-        Ok(DDragonClient {
-            version,
-            base_url,
-            client,
-            cache,
-        })
+        let cache: Cache = Arc::new(Mutex::new(ResponseCache::new()));
+        DDragonClient::with_cache(language, cache).await
+    }
+
+    /// Same as [`new`], but stores responses in the given cache,
+    /// which can be shared with other clients and inspected.
+    ///
+    /// [`new`]: #method.new
+    pub async fn with_cache(
+        language: LanguageCode,
+        cache: Cache,
+    ) -> Result<DDragonClient, ClientError> {
+        DDragonClient::new_for_lapi(construct_hyper_client(), cache, language, DDRAGON_HOST).await
+    }
+
+    /// Same as [`new`], but fetches everything from the given host
+    /// (e.g. an internal DDragon mirror) instead of `https://ddragon.leagueoflegends.com`.
+    ///
+    /// The host has to serve DDragon paths, starting with `/api/versions.json`.
+    ///
+    /// [`new`]: #method.new
+    pub async fn with_host(
+        language: LanguageCode,
+        host: &str,
+    ) -> Result<DDragonClient, ClientError> {
+        let cache: Cache = Arc::new(Mutex::new(ResponseCache::new()));
+        DDragonClient::new_for_lapi(construct_hyper_client(), cache, language, host).await
     }
 
+    /// Creates client sharing hyper client and cache with [`LeagueClient`],
+    /// both public constructors go through it as well.
+    ///
+    /// [`LeagueClient`]: ../api/struct.LeagueClient.html
     pub(crate) async fn new_for_lapi(
         client: Client,
         cache: Cache,
-        lang: LanguageCode,
+        language: LanguageCode,
+        host: &str,
     ) -> Result<DDragonClient, ClientError> {
-        let version = get_latest_ddragon_version(client.clone()).await?;
-        let base_url = format!(
-            "https://ddragon.leagueoflegends.com/cdn/{}/data/{}",
-            version, &lang
-        );
-        Ok(DDragonClient {
-            version,
+        let host = host.trim_end_matches('/');
+        let version = get_latest_ddragon_version(client.clone(), host).await?;
+        Ok(DDragonClient::pinned_for_lapi(
+            client, cache, language, host, &version,
+        ))
+    }
+
+    /// Same as [`new_for_lapi`], but pinned to the given version, so no request is made.
+    ///
+    /// [`new_for_lapi`]: #method.new_for_lapi
+    pub(crate) fn pinned_for_lapi(
+        client: Client,
+        cache: Cache,
+        language: LanguageCode,
+        host: &str,
+        version: &str,
+    ) -> DDragonClient {
+        let host = host.trim_end_matches('/').to_owned();
+        let base_url = data_url(&host, version, &language);
+        DDragonClient {
+            language,
+            version: version.to_owned(),
             client,
             cache,
+            host,
             base_url,
-        })
+            cache_dir: None,
+            revalidate_after: None,
+        }
+    }
+
+    /// Fetches all available DDragon versions, latest first.
+    ///
+    /// Any of them can be passed to [`with_version`].
+    ///
+    /// [`with_version`]: #method.with_version
+    pub async fn versions() -> Result<Vec<String>, ClientError> {
+        get_ddragon_versions(construct_hyper_client(), DDRAGON_HOST).await
+    }
+
+    /// Finds DDragon version with data of the patch a match was played on,
+    /// `game_version` is `gameVersion` of the match (e.g. `13.10.509.8402`).
+    ///
+    /// Game and DDragon versions only share major and minor parts, so the latest DDragon version
+    /// of the same patch is returned, or the closest older one if DDragon has no such patch.
+    /// Returns [`UnknownGameVersion`] if there is none.
+    /// Version list is fetched from the client's host and cached.
+    ///
+    /// [`UnknownGameVersion`]: ../error/enum.ClientError.html#variant.UnknownGameVersion
+    pub async fn closest_version(&mut self, game_version: &str) -> Result<String, ClientError> {
+        let url: Uri = parse_url(&format!("{}/api/versions.json", self.host))?;
+        let versions: Vec<String> = self.cached_resp(url).await?;
+        closest_version(&versions, game_version)
+            .cloned()
+            .context(UnknownGameVersion { game_version })
+    }
+
+    /// Pins client to the given DDragon version (e.g. `10.10.3216176`) instead of the latest one.
+    ///
+    /// Cached responses are keyed by full url, which contains the version,
+    /// so data of previously used version is never served after switching.
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.set_version(version);
+        self
+    }
+
+    /// Same as [`with_version`], but for already existing client.
+    ///
+    /// [`with_version`]: #method.with_version
+    pub fn set_version(&mut self, version: &str) {
+        self.version = version.to_owned();
+        self.base_url = data_url(&self.host, &self.version, &self.language);
+    }
+
+    /// Persists fetched data in the given directory and reads it from there before hitting the network,
+    /// so data of the same version is downloaded only once across process restarts.
+    ///
+    /// Only versioned data (`/cdn/{version}/data/...`) is persisted. Files are stored under their
+    /// DDragon path, which contains version and language, so data of other versions is never served.
+    /// Unversioned documents that change over time, like version list, realms and languages,
+    /// are always fetched from the network.
+    pub fn with_cache_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.cache_dir = Some(path.into());
+        self
+    }
+
+    /// Revalidates cached data once it is older than `ttl` instead of keeping it forever.
+    ///
+    /// Data is requested with `If-None-Match` set to its `ETag`, and a `304 Not Modified`
+    /// response reuses the cached body, so unchanged files are not downloaded again.
+    pub fn revalidate_after(mut self, ttl: Duration) -> Self {
+        self.revalidate_after = Some(ttl);
+        self
+    }
+
+    /// DDragon version this client fetches data for.
+    pub fn version(&self) -> &str {
+        &self.version
     }
 
+    /// Get short data of all champions.
     pub async fn get_champions(&mut self) -> Result<AllChampions, ClientError> {
-        let url: Uri = format!("{}/champion.json", &self.base_url).parse().unwrap();
+        let url: Uri = parse_url(&format!("{}/champion.json", &self.base_url))?;
         self.cached_resp(url).await
     }
 
+    /// Get full data of a champion by its DDragon id (e.g. `LeeSin`).
+    ///
+    /// Returns [`ChampionNotFound`] if there is no such champion in current version.
+    ///
+    /// [`ChampionNotFound`]: ../error/enum.ClientError.html#variant.ChampionNotFound
     pub async fn get_champion(&mut self, name: &str) -> Result<ChampionFullData, ClientError> {
-        let name = name.to_owned();
-        let url: Uri = format!("{}/champion/{}.json", &self.base_url, &name)
-            .parse()
-            .unwrap();
+        // Unknown names make DDragon respond with an error page instead of json,
+        // so check the champion list (which is cached anyway) first.
+        let champions = self.get_champions().await?;
+        ensure!(champions.data.contains_key(name), ChampionNotFound { name });
+        let url: Uri = parse_url(&format!("{}/champion/{}.json", &self.base_url, name))?;
         let mut resp = self.cached_resp::<ChampionExtended>(url).await?;
-        Ok(resp.data.remove(&name).unwrap())
+        resp.data.remove(name).context(ChampionNotFound { name })
+    }
+
+    /// Get full data of a champion by its numeric key (e.g. `64` for Lee Sin),
+    /// which is how League API references champions.
+    ///
+    /// Returns [`ChampionNotFound`] if there is no such champion in current version.
+    ///
+    /// [`ChampionNotFound`]: ../error/enum.ClientError.html#variant.ChampionNotFound
+    pub async fn get_champion_by_key(&mut self, key: u64) -> Result<ChampionFullData, ClientError> {
+        let champions = self.get_champions().await?;
+        let id = champions
+            .by_key(key)
+            .map(|champion| champion.id.clone())
+            .context(ChampionNotFound {
+                name: key.to_string(),
+            })?;
+        self.get_champion(&id).await
+    }
+
+    /// Get data of all items.
+    pub async fn get_items(&mut self) -> Result<AllItems, ClientError> {
+        let url: Uri = parse_url(&format!("{}/item.json", &self.base_url))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get all rune trees with their slots and runes.
+    pub async fn get_runes_reforged(&mut self) -> Result<Vec<RuneTree>, ClientError> {
+        // Unlike other DDragon files this one is a plain json array without `data` wrapper
+        let url: Uri = parse_url(&format!("{}/runesReforged.json", &self.base_url))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get data of all summoner spells.
+    pub async fn get_summoner_spells(&mut self) -> Result<AllSummonerSpells, ClientError> {
+        let url: Uri = parse_url(&format!("{}/summoner.json", &self.base_url))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get summoner spell by its numeric key (e.g. `4` for Flash),
+    /// which is how matches reference summoner spells.
+    ///
+    /// Returns [`SummonerSpellNotFound`] if there is no such spell in current version.
+    ///
+    /// [`SummonerSpellNotFound`]: ../error/enum.ClientError.html#variant.SummonerSpellNotFound
+    pub async fn summoner_spell_by_key(
+        &mut self,
+        key: u32,
+    ) -> Result<SummonerSpellData, ClientError> {
+        let spells = self.get_summoner_spells().await?;
+        spells
+            .by_key(key)
+            .cloned()
+            .context(SummonerSpellNotFound { key })
+    }
+
+    /// Get all locales DDragon has data for, e.g. to show only available languages.
+    pub async fn languages(&mut self) -> Result<Vec<String>, ClientError> {
+        let url: Uri = parse_url(&format!("{}/languages.json", self.cdn_url()))?;
+        self.cached_resp(url).await
+    }
+
+    /// Get versions of data used by live server of the realm (e.g. `na`, `euw`),
+    /// which may lag behind the latest DDragon version.
+    pub async fn realm(&mut self, region: &str) -> Result<Realm, ClientError> {
+        let url: Uri = parse_url(&format!(
+            "{}/realms/{}.json",
+            self.host,
+            region.to_ascii_lowercase()
+        ))?;
+        self.cached_resp(url).await
+    }
+
+    /// Host all data and images are fetched from, `https://ddragon.leagueoflegends.com` by default.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn cdn_url(&self) -> String {
+        format!("{}/cdn", self.host)
+    }
+
+    /// Url of square champion icon, `champion_id` is DDragon id (e.g. `LeeSin`).
+    pub fn champion_square_url(&self, champion_id: &str) -> String {
+        format!(
+            "{}/{}/img/champion/{}.png",
+            self.cdn_url(),
+            self.version,
+            champion_id
+        )
+    }
+
+    /// Url of champion skin splash art. Splash arts are not versioned.
+    pub fn champion_splash_url(&self, champion_id: &str, skin_num: u32) -> String {
+        format!(
+            "{}/img/champion/splash/{}_{}.jpg",
+            self.cdn_url(),
+            champion_id,
+            skin_num
+        )
+    }
+
+    /// Urls of splash arts of every skin of the champion, in order of its `skins`.
+    pub fn champion_splash_urls(&self, champion: &ChampionFullData) -> Vec<String> {
+        champion
+            .skins
+            .iter()
+            .map(|skin| self.champion_splash_url(&champion.id, skin.num as u32))
+            .collect()
+    }
+
+    /// Url of champion skin loading screen art. Loading screen arts are not versioned.
+    pub fn champion_loading_url(&self, champion_id: &str, skin_num: u32) -> String {
+        format!(
+            "{}/img/champion/loading/{}_{}.jpg",
+            self.cdn_url(),
+            champion_id,
+            skin_num
+        )
+    }
+
+    /// Url of champion passive icon, `image` is `image.full` of the passive.
+    pub fn passive_icon_url(&self, image: &str) -> String {
+        format!("{}/{}/img/passive/{}", self.cdn_url(), self.version, image)
+    }
+
+    /// Url of champion or summoner spell icon, `image` is `image.full` of the spell.
+    pub fn spell_icon_url(&self, image: &str) -> String {
+        format!("{}/{}/img/spell/{}", self.cdn_url(), self.version, image)
+    }
+
+    /// Url of item icon, `item_id` is a key of [`AllItems`] data.
+    ///
+    /// [`AllItems`]: ../dto/ddragon/struct.AllItems.html
+    pub fn item_icon_url(&self, item_id: &str) -> String {
+        format!(
+            "{}/{}/img/item/{}.png",
+            self.cdn_url(),
+            self.version,
+            item_id
+        )
+    }
+
+    /// Url of summoner profile icon.
+    pub fn profile_icon_url(&self, profile_icon_id: i32) -> String {
+        format!(
+            "{}/{}/img/profileicon/{}.png",
+            self.cdn_url(),
+            self.version,
+            profile_icon_id
+        )
+    }
+
+    fn cache_file(&self, url: &Uri) -> Option<PathBuf> {
+        let dir = self.cache_dir.as_ref()?;
+        // Only `/cdn/{version}/data/...` is immutable, host may add its own prefix before it
+        let versioned = url
+            .path()
+            .split('/')
+            .skip_while(|segment| *segment != "cdn")
+            .nth(2)
+            == Some("data");
+        if !versioned {
+            return None;
+        }
+        Some(dir.join(url.path().trim_start_matches('/')))
+    }
+
+    // DDragon files are small enough to be read and written in place without async fs
+    fn read_cache_file(&self, url: &Uri) -> Option<String> {
+        fs::read_to_string(self.cache_file(url)?).ok()
+    }
+
+    fn write_cache_file(&self, url: &Uri, data: &str) {
+        let path = match self.cache_file(url) {
+            Some(path) => path,
+            None => return,
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, data));
+        if let Err(e) = written {
+            warn!("Could not write DDragon cache file {:?}: {}", path, e);
+        }
     }
 }
 
+/// Riot's DDragon host used unless another one is given to [`DDragonClient::with_host`]
+///
+/// [`DDragonClient::with_host`]: struct.DDragonClient.html#method.with_host
+pub(crate) const DDRAGON_HOST: &str = "https://ddragon.leagueoflegends.com";
+
+fn data_url(host: &str, version: &str, language: &LanguageCode) -> String {
+    format!("{}/cdn/{}/data/{}", host, version, language)
+}
+
+/// Latest version of the same patch as `game_version`, or of the closest older one.
+/// `versions` are ordered latest first, as DDragon returns them.
+fn closest_version<'a>(versions: &'a [String], game_version: &str) -> Option<&'a String> {
+    let target = patch(game_version)?;
+    let mut closest: Option<(&String, (u32, u32))> = None;
+    for version in versions {
+        let version_patch = match patch(version) {
+            Some(p) if p <= target => p,
+            _ => continue,
+        };
+        // Strictly greater, so the first (latest) version of the patch wins
+        if closest.map_or(true, |(_, best)| version_patch > best) {
+            closest = Some((version, version_patch));
+        }
+    }
+    closest.map(|(version, _)| version)
+}
+
+/// Major and minor parts of a version, e.g. `(13, 10)` for `13.10.1`
+fn patch(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
 #[async_trait]
 impl CachedClient for DDragonClient {
     async fn cached_resp<T: Debug + DeserializeOwned + Send>(
         &self,
         url: Uri,
     ) -> Result<T, ClientError> {
-        let maybe_resp: Option<T> = self
+        let key = cache_key(&url);
+        let maybe_resp: Option<Result<T, _>> = self
             .cache
-            .lock()
-            .get(&url)
-            .map(|res| serde_json::from_str(res).unwrap());
+            .get(&key)
+            .await
+            .map(|payload| serde_json::from_str(&payload));
 
         if let Some(resp) = maybe_resp {
+            let resp: T = resp.context(Deserialization {
+                url: url.to_string(),
+            })?;
             debug!("Found cached: {:?}", resp);
             Ok(resp)
         } else {
+            // An expired entry has to be revalidated, the cache dir would serve it as fresh forever
+            let stale = self.cache.get_stale_with_etag(&key).await;
+            if stale.is_none() {
+                if let Some(string_response) = self.read_cache_file(&url) {
+                    debug!("Found in cache dir");
+                    let deserialized: T =
+                        serde_json::from_str(&string_response).context(Deserialization {
+                            url: url.to_string(),
+                        })?;
+                    self.cache.insert(&key, string_response, None).await;
+                    return Ok(deserialized);
+                }
+            }
             debug!("Nothing in cache. Fetching...");
             // We got nothing in cache, try fetching from utl
-            let req = Request::builder()
-                .uri(url.clone())
-                .body(Default::default())
-                .unwrap();
+            let mut req = Request::builder().uri(url.clone());
+            if let Some((_, etag)) = &stale {
+                req = req.header(IF_NONE_MATCH, etag.as_str());
+            }
+            let req = req.body(Default::default()).unwrap();
             let resp = self.client.request(req).await.context(HyperError)?;
+            if let (StatusCode::NOT_MODIFIED, Some((payload, etag))) = (resp.status(), stale) {
+                debug!("Not modified, reusing cached");
+                let deserialized: T = serde_json::from_str(&payload).context(Deserialization {
+                    url: url.to_string(),
+                })?;
+                self.cache
+                    .insert_with_etag(&key, payload, self.revalidate_after, Some(etag))
+                    .await;
+                return Ok(deserialized);
+            }
+            // DDragon is not bound to any region, default one only ends up in 503 error
+            ClientError::check_response(
+                Region::default(),
+                &url,
+                resp.status().as_u16(),
+                resp.headers(),
+            )?;
+            let etag = resp
+                .headers()
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned);
             let body = resp.into_body();
-            let bytes = hyper::body::to_bytes(body).await.unwrap();
-            let string_response = String::from_utf8(bytes.to_vec()).unwrap();
-            //.context(FromUTF8Error);
+            let bytes = hyper::body::to_bytes(body).await.context(HyperError)?;
+            let string_response = String::from_utf8(bytes.to_vec()).context(FromUTF8Error)?;
             debug!("Deserializing...");
-            let deserialized: T = serde_json::from_str(&string_response).unwrap();
-            self.cache.lock().insert(url, string_response);
+            let deserialized: T =
+                serde_json::from_str(&string_response).context(Deserialization {
+                    url: url.to_string(),
+                })?;
+            self.write_cache_file(&url, &string_response);
+            // DDragon data never changes for the same version, so it expires only if revalidation is enabled
+            self.cache
+                .insert_with_etag(&key, string_response, self.revalidate_after, etag)
+                .await;
             Ok(deserialized)
         }
     }
@@ -114,12 +526,226 @@ impl CachedClient for DDragonClient {
 
 #[cfg(test)]
 mod tests {
+    use crate::cache::{Cache, ResponseCache};
     use crate::constants::LanguageCode;
-    use crate::ddragon::DDragonClient;
+    use crate::ddragon::{data_url, DDragonClient, DDRAGON_HOST};
     use crate::dto::ddragon::{AllChampions, ChampionFullData};
-    use std::time::Instant;
+    use crate::error::ClientError;
+    use crate::utils::construct_hyper_client;
+    use crate::utils::mock::{MockResponse, MockServer};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    fn offline_client(base_url: &str) -> DDragonClient {
+        DDragonClient {
+            client: construct_hyper_client(),
+            cache: Arc::new(Mutex::new(ResponseCache::new())),
+            language: LanguageCode::UNITED_STATES,
+            version: "10.10.3216176".to_owned(),
+            host: DDRAGON_HOST.to_owned(),
+            base_url: base_url.to_owned(),
+            cache_dir: None,
+            revalidate_after: None,
+        }
+    }
+
+    /// Same as `offline_client`, but with DDragon path layout, as only versioned data goes to cache dir
+    fn versioned_client(host: &str) -> DDragonClient {
+        let base_url = data_url(host, "10.10.3216176", &LanguageCode::UNITED_STATES);
+        let mut client = offline_client(&base_url);
+        client.host = host.to_owned();
+        client
+    }
+
+    #[test]
+    fn errors_on_empty_version_list() {
+        let server = MockServer::start(vec![MockResponse::new(200, "[]")]);
+        smol::run(async {
+            let err = DDragonClient::with_host(LanguageCode::UNITED_STATES, server.url())
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, ClientError::NoDDragonVersions { ref host } if *host == server.url())
+            );
+        })
+    }
+
+    #[test]
+    fn picks_closest_version() {
+        let versions: Vec<String> = ["13.11.1", "13.10.2", "13.10.1", "13.8.1", "lolpatch_3.7"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        let closest = |game_version| super::closest_version(&versions, game_version);
+        assert_eq!(closest("13.10.509.8402").unwrap(), "13.10.2");
+        assert_eq!(closest("13.9.1").unwrap(), "13.8.1");
+        assert_eq!(closest("14.1.1").unwrap(), "13.11.1");
+        assert!(closest("12.23.1").is_none());
+        assert!(closest("garbage").is_none());
+    }
+
+    #[test]
+    fn resolves_game_version_from_host() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"["13.11.1", "13.10.1", "13.9.1"]"#,
+        )]);
+        smol::run(async {
+            let mut client = offline_client("");
+            client.host = server.url().to_owned();
+            assert_eq!(client.closest_version("13.10.1").await.unwrap(), "13.10.1");
+            let err = client.closest_version("12.1.1").await.unwrap_err();
+            assert!(matches!(err, ClientError::UnknownGameVersion { .. }));
+        });
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(server.requests()[0].path, "/api/versions.json");
+    }
+
+    #[test]
+    fn uses_custom_host() {
+        let server = MockServer::start(vec![MockResponse::new(200, r#"["10.10.3216176"]"#)]);
+        smol::run(async {
+            let client = DDragonClient::with_host(LanguageCode::UNITED_STATES, server.url())
+                .await
+                .unwrap();
+            assert_eq!(client.host(), server.url());
+            assert_eq!(
+                client.base_url,
+                format!("{}/cdn/10.10.3216176/data/en_US", server.url())
+            );
+            assert!(client.champion_square_url("Ahri").starts_with(server.url()));
+            assert_eq!(server.requests()[0].path, "/api/versions.json");
+        })
+    }
+
+    #[test]
+    fn builds_image_urls() {
+        let client = offline_client("");
+        let cdn = "https://ddragon.leagueoflegends.com/cdn";
+        assert_eq!(
+            client.champion_square_url("Ahri"),
+            format!("{}/10.10.3216176/img/champion/Ahri.png", cdn)
+        );
+        assert_eq!(
+            client.champion_splash_url("Ahri", 1),
+            format!("{}/img/champion/splash/Ahri_1.jpg", cdn)
+        );
+        assert_eq!(
+            client.champion_loading_url("Ahri", 0),
+            format!("{}/img/champion/loading/Ahri_0.jpg", cdn)
+        );
+        assert_eq!(
+            client.passive_icon_url("Ahri_SoulEater2.png"),
+            format!("{}/10.10.3216176/img/passive/Ahri_SoulEater2.png", cdn)
+        );
+        assert_eq!(
+            client.spell_icon_url("SummonerFlash.png"),
+            format!("{}/10.10.3216176/img/spell/SummonerFlash.png", cdn)
+        );
+        assert_eq!(
+            client.item_icon_url("1001"),
+            format!("{}/10.10.3216176/img/item/1001.png", cdn)
+        );
+        assert_eq!(
+            client.profile_icon_url(4568),
+            format!("{}/10.10.3216176/img/profileicon/4568.png", cdn)
+        );
+    }
+
+    #[test]
+    fn revalidates_with_etag() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "[]").header("ETag", "\"runes-v1\""),
+            MockResponse::new(304, ""),
+        ]);
+        smol::run(async {
+            let mut client = offline_client(server.url()).revalidate_after(Duration::from_secs(0));
+            assert!(client.get_runes_reforged().await.unwrap().is_empty());
+            assert!(client.get_runes_reforged().await.unwrap().is_empty());
+        });
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].header("if-none-match"), None);
+        assert_eq!(requests[1].header("if-none-match"), Some("\"runes-v1\""));
+    }
+
+    #[test]
+    fn revalidates_with_etag_when_using_cache_dir() {
+        let dir = std::env::temp_dir().join(format!("narwhalol-revalidate-{}", std::process::id()));
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "[]").header("ETag", "\"runes-v1\""),
+            MockResponse::new(304, ""),
+        ]);
+        smol::run(async {
+            let mut client = versioned_client(server.url())
+                .with_cache_dir(&dir)
+                .revalidate_after(Duration::from_secs(0));
+            assert!(client.get_runes_reforged().await.unwrap().is_empty());
+            assert!(client.get_runes_reforged().await.unwrap().is_empty());
+        });
+        std::fs::remove_dir_all(&dir).unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("if-none-match"), Some("\"runes-v1\""));
+    }
+
+    #[test]
+    fn serves_data_from_cache_dir() {
+        let dir = std::env::temp_dir().join(format!("narwhalol-ddragon-{}", std::process::id()));
+        let server = MockServer::start(vec![MockResponse::new(200, "[]")]);
+        smol::run(async {
+            let mut client = versioned_client(server.url()).with_cache_dir(&dir);
+            assert!(client.get_runes_reforged().await.unwrap().is_empty());
+            drop(client);
+            let mut client = versioned_client(server.url()).with_cache_dir(&dir);
+            assert!(client.get_runes_reforged().await.unwrap().is_empty());
+        });
+        assert_eq!(server.requests().len(), 1);
+        assert!(dir
+            .join("cdn/10.10.3216176/data/en_US/runesReforged.json")
+            .exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_reuse_unversioned_files_from_cache_dir() {
+        let dir = std::env::temp_dir().join(format!("narwhalol-versions-{}", std::process::id()));
+        let stale_versions = dir.join("api/versions.json");
+        std::fs::create_dir_all(stale_versions.parent().unwrap()).unwrap();
+        std::fs::write(&stale_versions, r#"["10.9.1"]"#).unwrap();
+        let server = MockServer::start(vec![
+            MockResponse::new(200, r#"["10.10.3216176", "10.9.1"]"#),
+            MockResponse::new(200, r#"["en_US"]"#),
+        ]);
+        smol::run(async {
+            let mut client = versioned_client(server.url()).with_cache_dir(&dir);
+            let version = client.closest_version("10.10.322.1234").await.unwrap();
+            assert_eq!(version, "10.10.3216176");
+            assert_eq!(client.languages().await.unwrap(), vec!["en_US"]);
+        });
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&stale_versions).unwrap(),
+            r#"["10.9.1"]"#
+        );
+        assert!(!dir.join("cdn/languages.json").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_cache_error_responses() {
+        let server = MockServer::start(vec![MockResponse::new(404, "<Error>NoSuchKey</Error>")]);
+        smol::run(async {
+            let mut client = offline_client(server.url());
+            let err = client.get_items().await.unwrap_err();
+            assert!(matches!(err, ClientError::DataNotFound { .. }));
+            assert_eq!(client.cache.stats().entries, 0);
+        })
+    }
 
     #[test]
+    #[ignore = "hits DDragon CDN"]
     fn creates_proper_instance() {
         smol::run(async {
             let cli = DDragonClient::new(LanguageCode::RUSSIA).await.unwrap();
@@ -128,6 +754,26 @@ mod tests {
     }
 
     #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn shares_given_cache() {
+        smol::run(async {
+            let cache: Cache = Arc::new(Mutex::new(ResponseCache::new()));
+            let mut first = DDragonClient::with_cache(LanguageCode::UNITED_STATES, cache.clone())
+                .await
+                .unwrap();
+            first.get_champions().await.unwrap();
+            let mut second = DDragonClient::with_cache(LanguageCode::UNITED_STATES, cache.clone())
+                .await
+                .unwrap();
+            second.get_champions().await.unwrap();
+            let stats = cache.stats();
+            assert_eq!(stats.entries, 1);
+            assert_eq!(stats.hits, 1);
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
     fn gets_full_champion_data() {
         smol::run(async {
             let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
@@ -137,4 +783,181 @@ mod tests {
             assert_eq!(xayah.name, "Xayah");
         })
     }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_champion_spells_passive_and_tips() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let lee_sin = client.get_champion("LeeSin").await.unwrap();
+            assert_eq!(lee_sin.spells.len(), 4);
+            assert!(!lee_sin.passive.name.is_empty());
+            assert!(!lee_sin.allytips.is_empty());
+            assert!(!lee_sin.enemytips.is_empty());
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn builds_splash_urls_of_all_skins() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let ahri = client.get_champion("Ahri").await.unwrap();
+            let urls = client.champion_splash_urls(&ahri);
+            assert!(urls.len() > 1);
+            assert_eq!(urls.len(), ahri.skins.len());
+            assert_eq!(urls[0], client.champion_splash_url("Ahri", 0));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_summoner_spell_by_key() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let flash = client.summoner_spell_by_key(4).await.unwrap();
+            assert_eq!(flash.id, "SummonerFlash");
+            let err = client.summoner_spell_by_key(9999).await.unwrap_err();
+            assert!(matches!(
+                err,
+                ClientError::SummonerSpellNotFound { key: 9999 }
+            ));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn returns_error_on_unknown_champion() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let err = client.get_champion("NotARealChamp").await.unwrap_err();
+            assert!(matches!(err, ClientError::ChampionNotFound { .. }));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn finds_champions_by_key_and_id() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let champions = client.get_champions().await.unwrap();
+            assert_eq!(champions.by_key(103).unwrap().name, "Ahri");
+            assert_eq!(champions.by_id("LeeSin").unwrap().key, "64");
+            assert!(champions.by_key(0).is_none());
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_languages() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let languages = client.languages().await.unwrap();
+            assert!(languages.iter().any(|l| l == "en_US"));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_realm() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let realm = client.realm("NA").await.unwrap();
+            assert_eq!(realm.l, "en_US");
+            assert!(realm.n.contains_key("champion"));
+            assert!(realm.cdn.starts_with("https://"));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_full_champion_data_by_key() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let lee_sin = client.get_champion_by_key(64).await.unwrap();
+            assert_eq!(lee_sin.id, "LeeSin");
+            let err = client.get_champion_by_key(0).await.unwrap_err();
+            assert!(matches!(err, ClientError::ChampionNotFound { .. }));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_items() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let items = client.get_items().await.unwrap();
+            assert_eq!(items.data["1001"].name, "Boots");
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_runes_reforged() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let trees = client.get_runes_reforged().await.unwrap();
+            let precision = trees.iter().find(|t| t.key == "Precision").unwrap();
+            assert!(!precision.slots.is_empty());
+            assert!(!precision.slots[0].runes.is_empty());
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_summoner_spells() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap();
+            let spells = client.get_summoner_spells().await.unwrap();
+            assert!(spells.data.contains_key("SummonerFlash"));
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn pins_version() {
+        smol::run(async {
+            let mut client = DDragonClient::new(LanguageCode::UNITED_STATES)
+                .await
+                .unwrap()
+                .with_version("10.10.3216176");
+            assert_eq!(client.version(), "10.10.3216176");
+            let champions = client.get_champions().await.unwrap();
+            assert_eq!(champions.version, "10.10.3216176");
+        })
+    }
+
+    #[test]
+    #[ignore = "hits DDragon CDN"]
+    fn gets_versions() {
+        smol::run(async {
+            let versions = DDragonClient::versions().await.unwrap();
+            assert!(!versions.is_empty());
+            assert!(versions[0]
+                .split('.')
+                .all(|part| part.parse::<u32>().is_ok()));
+        })
+    }
 }
@@ -1,6 +1,11 @@
-use serde::Deserialize;
+use crate::dto::ddragon::{ChampionData, ChampionFullData, ItemData, SummonerSpellData};
+use crate::error::ClientError;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct Summoner {
     pub profile_icon_id: i32,
@@ -12,7 +17,17 @@ pub struct Summoner {
     pub account_id: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[cfg(feature = "chrono")]
+impl Summoner {
+    /// [`revision_date`] as a UTC timestamp.
+    ///
+    /// [`revision_date`]: #structfield.revision_date
+    pub fn revision_datetime(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.revision_date as i64).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampionInfo {
     pub free_champion_ids: Vec<i64>,
@@ -20,7 +35,7 @@ pub struct ChampionInfo {
     pub max_new_player_level: i64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampionMastery {
     pub chest_granted: bool,
@@ -34,7 +49,7 @@ pub struct ChampionMastery {
     pub summoner_id: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct LeagueInfo {
     pub queue_type: String,
@@ -51,3 +66,536 @@ pub struct LeagueInfo {
     pub summoner_id: String,
     pub league_points: i64,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueList {
+    pub league_id: String,
+    pub tier: String,
+    pub name: String,
+    pub queue: String,
+    pub entries: Vec<LeagueItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueItem {
+    pub summoner_id: String,
+    pub summoner_name: String,
+    pub rank: String,
+    pub league_points: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub hot_streak: bool,
+    pub veteran: bool,
+    pub inactive: bool,
+    pub fresh_blood: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub metadata: MatchMetadata,
+    pub info: MatchInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchMetadata {
+    pub data_version: String,
+    pub match_id: String,
+    pub participants: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchInfo {
+    pub game_creation: i64,
+    pub game_duration: i64,
+    pub game_id: i64,
+    pub game_mode: String,
+    pub game_type: String,
+    pub game_version: String,
+    pub map_id: i32,
+    pub platform_id: String,
+    pub queue_id: i32,
+    pub participants: Vec<MatchParticipant>,
+    pub teams: Vec<MatchTeam>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchParticipant {
+    pub puuid: String,
+    pub summoner_id: String,
+    #[serde(default)]
+    pub summoner_name: String,
+    pub participant_id: i32,
+    pub team_id: i32,
+    pub champion_id: i64,
+    pub champion_name: String,
+    pub champ_level: i32,
+    pub summoner1_id: i32,
+    pub summoner2_id: i32,
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+    pub gold_earned: i32,
+    pub total_minions_killed: i32,
+    pub item0: i32,
+    pub item1: i32,
+    pub item2: i32,
+    pub item3: i32,
+    pub item4: i32,
+    pub item5: i32,
+    pub item6: i32,
+    pub team_position: String,
+    pub win: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchTeam {
+    pub team_id: i32,
+    pub win: bool,
+    pub bans: Vec<MatchBan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchBan {
+    pub champion_id: i64,
+    pub pick_turn: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatchTimeline {
+    pub metadata: MatchMetadata,
+    pub info: TimelineInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineInfo {
+    pub frame_interval: i64,
+    pub frames: Vec<TimelineFrame>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineFrame {
+    pub timestamp: i64,
+    pub events: Vec<TimelineEvent>,
+    pub participant_frames: HashMap<String, ParticipantFrame>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantFrame {
+    pub participant_id: i32,
+    pub level: i32,
+    pub xp: i32,
+    pub current_gold: i32,
+    pub total_gold: i32,
+    pub minions_killed: i32,
+    pub jungle_minions_killed: i32,
+    pub position: Option<TimelinePosition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct TimelinePosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Timeline event tagged by its `type` field.
+///
+/// Event kinds that are not modeled here deserialize into `Unknown`,
+/// so new events added by Riot do not break parsing of the whole timeline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineEvent {
+    #[serde(rename_all = "camelCase")]
+    ChampionKill {
+        timestamp: i64,
+        killer_id: i32,
+        victim_id: i32,
+        #[serde(default)]
+        assisting_participant_ids: Vec<i32>,
+        position: TimelinePosition,
+    },
+    #[serde(rename_all = "camelCase")]
+    ItemPurchased {
+        timestamp: i64,
+        participant_id: i32,
+        item_id: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    ItemSold {
+        timestamp: i64,
+        participant_id: i32,
+        item_id: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    ItemDestroyed {
+        timestamp: i64,
+        participant_id: i32,
+        item_id: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    SkillLevelUp {
+        timestamp: i64,
+        participant_id: i32,
+        skill_slot: i32,
+        level_up_type: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    LevelUp {
+        timestamp: i64,
+        participant_id: i32,
+        level: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    WardPlaced {
+        timestamp: i64,
+        creator_id: i32,
+        ward_type: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    WardKill {
+        timestamp: i64,
+        killer_id: i32,
+        ward_type: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    BuildingKill {
+        timestamp: i64,
+        killer_id: i32,
+        team_id: i32,
+        building_type: String,
+        lane_type: String,
+        position: TimelinePosition,
+    },
+    #[serde(rename_all = "camelCase")]
+    EliteMonsterKill {
+        timestamp: i64,
+        killer_id: i32,
+        killer_team_id: i32,
+        monster_type: String,
+        monster_sub_type: Option<String>,
+        position: TimelinePosition,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentGameInfo {
+    pub game_id: i64,
+    pub game_type: String,
+    pub game_start_time: i64,
+    pub map_id: i64,
+    pub game_length: i64,
+    pub platform_id: String,
+    pub game_mode: String,
+    pub banned_champions: Vec<BannedChampion>,
+    pub game_queue_config_id: Option<i64>,
+    pub participants: Vec<CurrentGameParticipant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentGameParticipant {
+    pub champion_id: i64,
+    pub profile_icon_id: i64,
+    pub bot: bool,
+    pub team_id: i64,
+    pub summoner_name: String,
+    pub summoner_id: String,
+    pub spell1_id: i64,
+    pub spell2_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedChampion {
+    pub pick_turn: i32,
+    pub champion_id: i64,
+    pub team_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedGames {
+    pub game_list: Vec<FeaturedGameInfo>,
+    pub client_refresh_interval: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedGameInfo {
+    pub game_id: i64,
+    pub game_type: String,
+    pub game_start_time: i64,
+    pub map_id: i64,
+    pub game_length: i64,
+    pub platform_id: String,
+    pub game_mode: String,
+    pub banned_champions: Vec<BannedChampion>,
+    pub game_queue_config_id: Option<i64>,
+    pub participants: Vec<FeaturedGameParticipant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedGameParticipant {
+    pub champion_id: i64,
+    pub profile_icon_id: i64,
+    pub bot: bool,
+    pub team_id: i64,
+    pub summoner_name: String,
+    pub spell1_id: i64,
+    pub spell2_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub puuid: String,
+    pub game_name: Option<String>,
+    pub tag_line: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashPlayer {
+    pub summoner_id: String,
+    pub team_id: Option<String>,
+    pub position: String,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTeam {
+    pub id: String,
+    pub tournament_id: i32,
+    pub name: String,
+    pub icon_id: i32,
+    pub tier: i32,
+    /// Summoner id of the team captain
+    pub captain: String,
+    pub abbreviation: String,
+    pub players: Vec<ClashPlayer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTournament {
+    pub id: i32,
+    pub theme_id: i32,
+    pub name_key: String,
+    pub name_key_secondary: String,
+    pub schedule: Vec<ClashTournamentPhase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTournamentPhase {
+    pub id: i32,
+    pub registration_time: i64,
+    pub start_time: i64,
+    pub cancelled: bool,
+}
+
+/// Free champion rotation resolved to DDragon data, see
+/// [`get_champion_rotation`](../../api/struct.LeagueClient.html#method.get_champion_rotation)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ChampionRotation {
+    /// Free for everyone
+    pub regular: Vec<ChampionFullData>,
+    /// Free for players of `beginner_max_level` or lower
+    pub beginner: Vec<ChampionFullData>,
+    pub beginner_max_level: u8,
+}
+
+/// Overview of summoner's champion masteries, see
+/// [`get_mastery_summary`](../../api/struct.LeagueClient.html#method.get_mastery_summary)
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct MasterySummary {
+    /// Sum of mastery levels of all champions
+    pub total_score: i32,
+    /// Number of champions with any mastery
+    pub champion_count: usize,
+    /// Up to 3 masteries with the most champion points
+    pub top_champions: Vec<ChampionMastery>,
+}
+
+/// Challenges progress of a player
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerInfo {
+    pub total_points: ChallengePoints,
+    /// Keyed by category, e.g. `COLLECTION` or `IMAGINATION`
+    pub category_points: HashMap<String, ChallengePoints>,
+    pub challenges: Vec<ChallengeInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengePoints {
+    /// Level name, e.g. `GOLD`, or `NONE` if nothing is achieved yet
+    pub level: String,
+    pub current: i64,
+    pub max: i64,
+    pub percentile: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeInfo {
+    pub challenge_id: i64,
+    pub percentile: f64,
+    pub level: String,
+    pub value: f64,
+    /// Epoch milliseconds, absent if the level was never reached
+    pub achieved_time: Option<i64>,
+}
+
+/// Match with its participants joined to DDragon data, see
+/// [`get_match_enriched`](../../api/struct.LeagueClient.html#method.get_match_enriched)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EnrichedMatch {
+    pub data: Match,
+    /// In the same order as `data.info.participants`
+    pub participants: Vec<EnrichedParticipant>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EnrichedParticipant {
+    pub participant_id: i32,
+    pub champion: ChampionData,
+    /// Spells not found in DDragon data (e.g. mode specific ones) are skipped
+    pub summoner_spells: Vec<SummonerSpellData>,
+    /// Empty slots and items not found in DDragon data are skipped
+    pub items: Vec<ItemData>,
+}
+
+/// Participant of a live game with champion and ranked entries resolved, see
+/// [`enrich_live_game`](../../api/struct.LeagueClient.html#method.enrich_live_game)
+#[derive(Debug)]
+pub struct LiveGameParticipant {
+    pub participant: CurrentGameParticipant,
+    /// `None` if the champion is missing in DDragon data, e.g. just released one
+    pub champion: Option<ChampionData>,
+    /// Ranked entries in every queue, failure to get them does not affect other participants
+    pub leagues: Result<Vec<LeagueInfo>, ClientError>,
+}
+
+/// Champion mastery with champion name and icon, see
+/// [`enrich_masteries`](../../api/struct.LeagueClient.html#method.enrich_masteries)
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct NamedMastery {
+    pub mastery: ChampionMastery,
+    /// Display name, e.g. `Lee Sin`
+    pub champion_name: String,
+    pub square_icon_url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LeagueList, Summoner, TimelineEvent};
+    use crate::constants::RankedTier;
+    use std::collections::HashSet;
+
+    #[test]
+    fn deserializes_unknown_timeline_event() {
+        let event: TimelineEvent =
+            serde_json::from_str(r#"{"type": "SOMETHING_NEW", "timestamp": 10}"#).unwrap();
+        assert!(matches!(event, TimelineEvent::Unknown));
+        let event: TimelineEvent = serde_json::from_str(
+            r#"{"type": "LEVEL_UP", "timestamp": 10, "participantId": 1, "level": 2}"#,
+        )
+        .unwrap();
+        assert!(matches!(event, TimelineEvent::LevelUp { level: 2, .. }));
+    }
+
+    #[test]
+    fn deserializes_unknown_tier_and_queue() {
+        // Tiers and queues are kept as raw strings, so values added by Riot never break parsing
+        let league: LeagueList = serde_json::from_str(
+            r#"{
+                "leagueId": "test-league",
+                "tier": "MYTHIC",
+                "name": "Narwhal's Tusks",
+                "queue": "RANKED_SOMETHING_NEW",
+                "entries": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(league.tier, "MYTHIC");
+        assert!(league.tier.parse::<RankedTier>().is_err());
+    }
+
+    #[test]
+    fn summoner_round_trips_through_json() {
+        let json = r#"{
+            "profileIconId": 4,
+            "name": "Vetro",
+            "puuid": "test-puuid",
+            "summonerLevel": 146,
+            "revisionDate": 1590000000000,
+            "id": "test-id",
+            "accountId": "test-account"
+        }"#;
+        let summoner: Summoner = serde_json::from_str(json).unwrap();
+        let serialized = serde_json::to_value(&summoner).unwrap();
+        // Serialized with Riot's casing, so it matches the original response
+        assert_eq!(
+            serialized,
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+        let back: Summoner = serde_json::from_value(serialized).unwrap();
+        assert_eq!(back.puuid, summoner.puuid);
+    }
+
+    #[test]
+    fn summoners_compare_and_hash_by_value() {
+        let json = r#"{
+            "profileIconId": 4,
+            "name": "Vetro",
+            "puuid": "test-puuid",
+            "summonerLevel": 146,
+            "revisionDate": 1590000000000,
+            "id": "test-id",
+            "accountId": "test-account"
+        }"#;
+        let summoner: Summoner = serde_json::from_str(json).unwrap();
+        let mut renamed = summoner.clone();
+        assert_eq!(renamed, summoner);
+        renamed.name = "Santorin".to_owned();
+        assert_ne!(renamed, summoner);
+        let set: HashSet<_> = vec![summoner.clone(), renamed, summoner]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn converts_revision_date_to_datetime() {
+        let json = r#"{
+            "profileIconId": 4,
+            "name": "Vetro",
+            "puuid": "test-puuid",
+            "summonerLevel": 146,
+            "revisionDate": 1590000000123,
+            "id": "test-id",
+            "accountId": "test-account"
+        }"#;
+        let summoner: Summoner = serde_json::from_str(json).unwrap();
+        let revision = summoner.revision_datetime();
+        assert_eq!(revision.to_rfc3339(), "2020-05-20T18:40:00.123+00:00");
+        assert_eq!(revision.timestamp_millis() as u64, summoner.revision_date);
+    }
+}
@@ -1,7 +1,7 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AllChampions {
     #[serde(rename = "type")]
     pub data_type: String,
@@ -10,7 +10,21 @@ pub struct AllChampions {
     pub data: HashMap<String, ChampionData>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl AllChampions {
+    /// Finds champion by numeric key (e.g. `64` for Lee Sin),
+    /// which is how League API references champions.
+    pub fn by_key(&self, key: u64) -> Option<&ChampionData> {
+        let key = key.to_string();
+        self.data.values().find(|champion| champion.key == key)
+    }
+
+    /// Finds champion by DDragon id (e.g. `LeeSin`).
+    pub fn by_id(&self, id: &str) -> Option<&ChampionData> {
+        self.data.get(id)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ChampionExtended {
     #[serde(rename = "type")]
     pub type_field: String,
@@ -19,7 +33,7 @@ pub struct ChampionExtended {
     pub data: HashMap<String, ChampionFullData>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ChampionData {
     pub version: String,
     pub id: String,
@@ -34,7 +48,7 @@ pub struct ChampionData {
     pub stats: ChampionStatsData,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct ChampionInfoData {
     pub attack: i32,
     pub defense: i32,
@@ -42,7 +56,7 @@ pub struct ChampionInfoData {
     pub difficulty: i32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct ChampionImageData {
     pub full: String,
     pub sprite: String,
@@ -53,7 +67,7 @@ pub struct ChampionImageData {
     pub h: i32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ChampionStatsData {
     pub hp: f64,
     pub hpperlevel: f64,
@@ -77,7 +91,7 @@ pub struct ChampionStatsData {
     pub attackspeed: f64,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ChampionFullData {
     pub id: String,
     pub key: String,
@@ -87,18 +101,21 @@ pub struct ChampionFullData {
     pub skins: Vec<ChampionSkinData>,
     pub lore: String,
     pub blurb: String,
+    /// Tips for playing the champion
     pub allytips: Vec<String>,
+    /// Tips for playing against the champion
     pub enemytips: Vec<String>,
     pub tags: Vec<String>,
     pub partype: String,
     pub info: ChampionInfoData,
     pub stats: ChampionStatsData,
+    /// Q, W, E and R in this order
     pub spells: Vec<ChampionSpellData>,
     pub passive: ChampionPassiveData,
     pub recommended: Vec<ChampionRecommendedData>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct ChampionSkinData {
     pub id: String,
     pub num: i32,
@@ -106,7 +123,7 @@ pub struct ChampionSkinData {
     pub chromas: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampionSpellData {
     pub id: String,
@@ -130,20 +147,20 @@ pub struct ChampionSpellData {
     pub image: ChampionImageData,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct ChampionSpellLevelTipData {
     pub label: Vec<String>,
     pub effect: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct ChampionPassiveData {
     pub name: String,
     pub description: String,
     pub image: ChampionImageData,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampionRecommendedData {
     pub champion: String,
@@ -162,11 +179,11 @@ pub struct ChampionRecommendedData {
     pub priority: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampionDataValues {}
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampionBlockData {
     #[serde(rename = "type")]
@@ -185,9 +202,115 @@ pub struct ChampionBlockData {
     pub items: Vec<ChampionItemData>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct ChampionItemData {
     pub id: String,
     pub count: i64,
     pub hide_count: Option<bool>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AllItems {
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub version: String,
+    pub data: HashMap<String, ItemData>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct ItemData {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub plaintext: String,
+    #[serde(default)]
+    pub into: Vec<String>,
+    #[serde(default)]
+    pub from: Vec<String>,
+    pub image: ChampionImageData,
+    pub gold: ItemGoldData,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct ItemGoldData {
+    pub base: i32,
+    pub purchasable: bool,
+    pub total: i32,
+    pub sell: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct RuneTree {
+    pub id: i64,
+    pub key: String,
+    pub icon: String,
+    pub name: String,
+    pub slots: Vec<RuneSlot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct RuneSlot {
+    pub runes: Vec<Rune>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct Rune {
+    pub id: i64,
+    pub key: String,
+    pub icon: String,
+    pub name: String,
+    pub short_desc: String,
+    pub long_desc: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AllSummonerSpells {
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub version: String,
+    pub data: HashMap<String, SummonerSpellData>,
+}
+
+impl AllSummonerSpells {
+    /// Finds summoner spell by numeric key (e.g. `4` for Flash),
+    /// which is how League API references summoner spells.
+    pub fn by_key(&self, key: u32) -> Option<&SummonerSpellData> {
+        let key = key.to_string();
+        self.data.values().find(|spell| spell.key == key)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SummonerSpellData {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub cooldown: Vec<f64>,
+    pub key: String,
+    pub summoner_level: i64,
+    pub modes: Vec<String>,
+    pub image: ChampionImageData,
+}
+
+/// Versions of DDragon data currently used by a live server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Realm {
+    /// Version of every data type, e.g. `champion` or `item`
+    pub n: HashMap<String, String>,
+    /// Current version of the realm
+    pub v: String,
+    /// Default language of the realm
+    pub l: String,
+    /// CDN host to load assets from
+    pub cdn: String,
+    /// Version of DDragon data files
+    pub dd: String,
+    /// Version of legacy client data
+    pub lg: String,
+    pub css: String,
+    pub profileiconmax: i64,
+    pub store: Option<String>,
+}
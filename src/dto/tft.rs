@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct TftLeagueEntry {
+    /// Missing for Hyper Roll entries
+    pub league_id: Option<String>,
+    pub summoner_id: String,
+    pub summoner_name: String,
+    pub queue_type: String,
+    /// Hyper Roll tier (e.g. `BLUE`), only present for Hyper Roll entries
+    pub rated_tier: Option<String>,
+    /// Hyper Roll rating, only present for Hyper Roll entries
+    pub rated_rating: Option<i64>,
+    pub tier: Option<String>,
+    pub rank: Option<String>,
+    pub league_points: Option<i64>,
+    pub wins: i64,
+    pub losses: i64,
+    pub hot_streak: Option<bool>,
+    pub veteran: Option<bool>,
+    pub fresh_blood: Option<bool>,
+    pub inactive: Option<bool>,
+    pub mini_series: Option<TftMiniSeries>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct TftMiniSeries {
+    pub losses: i32,
+    pub progress: String,
+    pub target: i32,
+    pub wins: i32,
+}
@@ -5,8 +5,11 @@ use crate::constants::Region;
 use futures::future::{err, ok};
 
 use futures::Future;
+use hyper::header::{HeaderMap, RETRY_AFTER};
+use hyper::Uri;
 use snafu::Snafu;
 use std::string::FromUtf8Error;
+use std::time::Duration;
 
 macro_rules! assert_matches {
     ($expression:expr, $($pattern:tt)+) => {
@@ -24,58 +27,163 @@ macro_rules! assert_matches {
 #[snafu(visibility(pub(crate)))]
 pub enum ClientError {
     /// Bad request
-    #[snafu(display("Got 400: Bad Request"))]
-    BadRequest,
+    #[snafu(display("Got 400: Bad Request at {}", url.as_deref().unwrap_or("unknown url")))]
+    BadRequest { url: Option<String> },
     /// Unauthorized
-    #[snafu(display("Got 401: Unauthorized"))]
-    Unauthorized,
+    #[snafu(display("Got 401: Unauthorized at {}", url.as_deref().unwrap_or("unknown url")))]
+    Unauthorized { url: Option<String> },
     /// Forbidden
-    #[snafu(display("Got 403: Forbidden"))]
-    Forbidden,
-    /// Data not found
-    #[snafu(display("Got 404: Data not found"))]
-    DataNotFound,
+    #[snafu(display("Got 403: Forbidden at {}", url.as_deref().unwrap_or("unknown url")))]
+    Forbidden { url: Option<String> },
+    /// Data not found. `url` is the requested url when the error comes from an actual request.
+    #[snafu(display(
+        "Got 404: Data not found at {}",
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    DataNotFound { url: Option<String> },
     /// Method is not allowwed
-    #[snafu(display("Got 405: Method not allowed"))]
-    MethodNotAllowed,
+    #[snafu(display(
+        "Got 405: Method not allowed at {}",
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    MethodNotAllowed { url: Option<String> },
     /// Unsupported media type
-    #[snafu(display("Got 415: Unsupported media type"))]
-    UnsupportedMediaType,
+    #[snafu(display(
+        "Got 415: Unsupported media type at {}",
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    UnsupportedMediaType { url: Option<String> },
     /// This error is returned when you have exceeded your rate limit for an api.
-    // TODO: Add rate-limiting
-    #[snafu(display("Got 429: Rate limit exceeded. limit: {}", limit))]
-    RateLimitExceeded { limit: usize },
+    /// `retry_after` and `limit_type` are taken from `Retry-After` and `X-Rate-Limit-Type` headers
+    /// when Riot provides them.
+    #[snafu(display(
+        "Got 429: Rate limit exceeded at {}. limit type: {:?}, retry after: {:?}",
+        url.as_deref().unwrap_or("unknown url"),
+        limit_type,
+        retry_after
+    ))]
+    RateLimitExceeded {
+        retry_after: Option<Duration>,
+        limit_type: Option<String>,
+        url: Option<String>,
+    },
     /// Internal server error
-    #[snafu(display("Got 500: Internal server error"))]
-    InternalServerError,
+    #[snafu(display(
+        "Got 500: Internal server error at {}",
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    InternalServerError { url: Option<String> },
     /// Bad gateway
-    #[snafu(display("Got 502: Bad Gateway"))]
-    BadGateway,
+    #[snafu(display("Got 502: Bad Gateway at {}", url.as_deref().unwrap_or("unknown url")))]
+    BadGateway { url: Option<String> },
     /// This error is returned when the riot api servers are on maintenance.
     /// Please visit https://developer.riotgames.com/api-status/ for more information
-    #[snafu(display("Got 503: Service unavailable for region {:?}", region))]
-    ServiceUnavailable { region: Region },
+    #[snafu(display(
+        "Got 503: Service unavailable for region {:?} at {}",
+        region,
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    ServiceUnavailable { region: Region, url: Option<String> },
     /// Gateway Timeout
-    #[snafu(display("Got 504: Gateway timeout"))]
-    GatewayTimeout,
+    #[snafu(display(
+        "Got 504: Gateway timeout at {}",
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    GatewayTimeout { url: Option<String> },
+    /// Any other non-success status Riot does not document
+    #[snafu(display(
+        "Got unexpected status {} at {}",
+        status,
+        url.as_deref().unwrap_or("unknown url")
+    ))]
+    UnexpectedStatus { status: u16, url: Option<String> },
 
-    /// Internal url not parsed error
+    /// This error is returned when DDragon has no champion with requested name
+    #[snafu(display("Champion {} was not found in DDragon data", name))]
+    ChampionNotFound { name: String },
+
+    /// This error is returned when DDragon has no summoner spell with requested key
+    #[snafu(display("Summoner spell {} was not found in DDragon data", key))]
+    SummonerSpellNotFound { key: u32 },
+
+    /// This error is returned when DDragon data is requested from [`LeagueClient`]
+    /// created without [`with_ddragon`]
+    ///
+    /// [`LeagueClient`]: ../api/struct.LeagueClient.html
+    /// [`with_ddragon`]: ../api/struct.LeagueClient.html#method.with_ddragon
+    #[snafu(display("LeagueClient has no embedded DDragon client, see with_ddragon"))]
+    DDragonNotAttached,
+
+    /// This error is returned when DDragon host responds with an empty version list
+    #[snafu(display("DDragon host {} returned no versions", host))]
+    NoDDragonVersions { host: String },
+
+    /// This error is returned when no DDragon version is old enough for the game version
+    #[snafu(display("No DDragon version matches game version {}", game_version))]
+    UnknownGameVersion { game_version: String },
+
+    /// This error is returned when string could not be parsed into a [`Region`]
+    ///
+    /// [`Region`]: ../constants/region/struct.Region.html
+    #[snafu(display("Unknown region: {}", input))]
+    UnknownRegion { input: String },
+
+    /// This error is returned when string could not be parsed into a [`LanguageCode`]
+    ///
+    /// [`LanguageCode`]: ../constants/lang_code/struct.LanguageCode.html
+    #[snafu(display("Unknown language code: {}", input))]
+    UnknownLanguageCode { input: String },
+
+    /// This error is returned when string could not be parsed into a [`RankedTier`]
+    ///
+    /// [`RankedTier`]: ../constants/ranked_tier/struct.RankedTier.html
+    #[snafu(display("Unknown ranked tier: {}", input))]
+    UnknownRankedTier { input: String },
+
+    /// This error is returned when string could not be parsed into a [`Division`]
+    ///
+    /// [`Division`]: ../constants/division/struct.Division.html
+    #[snafu(display("Unknown division: {}", input))]
+    UnknownDivision { input: String },
+
+    /// This error is returned when url built from provided values is not valid,
+    /// e.g. when an id contains a newline
     #[snafu(display("could not parse url"))]
     UrlNotParsed,
 
+    /// This error is returned when request did not complete within timeout
+    /// configured with [`LeagueClientBuilder::timeout`]
+    ///
+    /// [`LeagueClientBuilder::timeout`]: ../api/struct.LeagueClientBuilder.html#method.timeout
+    #[snafu(display("Request to {} timed out after {:?}", url, timeout))]
+    Timeout { url: String, timeout: Duration },
+
     /// Hyper error
     #[snafu(display("hyper errored: {}", source))]
     HyperError { source: hyper::Error },
     /// This error is returned when the user provides no token
     #[snafu(display("Please provide the correct RIOT_API_KEY variable because {}", source))]
     NoToken { source: std::env::VarError },
-    /// This error is returned when the user provides malformed token
+    /// This error is returned when the user provides malformed token.
+    /// `token` is redacted to its first characters and length, so the key never ends up in logs
     #[snafu(display("Provided token {} is not correct riot api token", token))]
     WrongToken { token: String },
 
+    /// This error is returned when HTTP proxy refuses to open a tunnel
+    #[snafu(display("Proxy refused to connect: {}", response))]
+    ProxyConnect { response: String },
+
     #[snafu(display("Unsupported scheme: {:?}", scheme))]
     UnsupportedScheme { scheme: Option<String> },
 
+    /// This error is returned when response body does not match expected type,
+    /// e.g. when Riot changes the shape of an endpoint
+    #[snafu(display("Could not deserialize response of {}: {}", url, source))]
+    Deserialization {
+        source: serde_json::Error,
+        url: String,
+    },
+
     #[snafu(display("Could not make string from ut8: {}", source))]
     FromUTF8Error { source: FromUtf8Error },
 
@@ -87,58 +195,223 @@ pub enum ClientError {
 }
 
 impl ClientError {
-    pub fn check_status(region: Region, code: u16) -> Result<(), ClientError> {
+    /// Error Riot means by the status code, `None` for success (2xx) codes.
+    /// Codes Riot does not document end up in `UnexpectedStatus`.
+    /// Errors made here carry no url, see [`check_response`] for the one attached to requests.
+    ///
+    /// [`check_response`]: #method.check_response
+    pub fn from_status(region: Region, code: u16) -> Option<ClientError> {
+        ClientError::from_status_at(region, code, None)
+    }
+
+    fn from_status_at(region: Region, code: u16, url: Option<String>) -> Option<ClientError> {
         use self::ClientError::*;
         match code {
-            400 => Err(BadRequest),
-            401 => Err(Unauthorized),
-            403 => Err(Forbidden),
-            404 => Err(DataNotFound),
-            405 => Err(MethodNotAllowed),
-            415 => Err(UnsupportedMediaType),
-            429 => Err(RateLimitExceeded { limit: 0_usize }),
-            500 => Err(InternalServerError),
-            502 => Err(BadGateway),
-            503 => Err(ServiceUnavailable { region }),
-            504 => Err(GatewayTimeout),
-            _ => Ok(()),
+            400 => Some(BadRequest { url }),
+            401 => Some(Unauthorized { url }),
+            403 => Some(Forbidden { url }),
+            404 => Some(DataNotFound { url }),
+            405 => Some(MethodNotAllowed { url }),
+            415 => Some(UnsupportedMediaType { url }),
+            429 => Some(RateLimitExceeded {
+                retry_after: None,
+                limit_type: None,
+                url,
+            }),
+            500 => Some(InternalServerError { url }),
+            502 => Some(BadGateway { url }),
+            503 => Some(ServiceUnavailable { region, url }),
+            504 => Some(GatewayTimeout { url }),
+            200..=299 => None,
+            status => Some(UnexpectedStatus { status, url }),
+        }
+    }
+
+    /// Same as [`from_status`], but as a `Result` that can be propagated with `?`.
+    ///
+    /// [`from_status`]: #method.from_status
+    pub fn check_status(region: Region, code: u16) -> Result<(), ClientError> {
+        match ClientError::from_status(region, code) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Same as [`check_status`], but also fills requested url
+    /// and rate limit details from response headers.
+    ///
+    /// [`check_status`]: #method.check_status
+    pub(crate) fn check_response(
+        region: Region,
+        url: &Uri,
+        code: u16,
+        headers: &HeaderMap,
+    ) -> Result<(), ClientError> {
+        match ClientError::from_status_at(region, code, Some(url.to_string())) {
+            Some(ClientError::RateLimitExceeded { url, .. }) => {
+                Err(ClientError::RateLimitExceeded {
+                    retry_after: headers
+                        .get(RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse().ok())
+                        .map(Duration::from_secs),
+                    limit_type: headers
+                        .get("X-Rate-Limit-Type")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_owned),
+                    url,
+                })
+            }
+            Some(err) => Err(err),
+            None => Ok(()),
         }
     }
 }
 
+/// Lets hyper errors be propagated with `?` from code that does not need extra context.
+/// Both [`LeagueClient`] and [`DDragonClient`] run on hyper, so this is the only transport error.
+///
+/// [`LeagueClient`]: ../api/struct.LeagueClient.html
+/// [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
+impl From<hyper::Error> for ClientError {
+    fn from(source: hyper::Error) -> ClientError {
+        ClientError::HyperError { source }
+    }
+}
+
 #[cfg(test)]
 mod api_error_tests {
     use super::*;
-    use crate::api::LeagueClient;
     use crate::constants::Region;
+    use hyper::header::HeaderValue;
+
+    fn status_error(code: u16) -> ClientError {
+        ClientError::from_status(Region::NA, code).unwrap()
+    }
 
     #[test]
     fn returns_correct_status_codes() {
-        let lapi = LeagueClient::new(Region::NA).unwrap();
-        let bad_r_err = lapi.get_status(400).unwrap_err();
-        let unauthorized_err = lapi.get_status(401).unwrap_err();
-        let forbidden_err = lapi.get_status(403).unwrap_err();
-        let not_found_err = lapi.get_status(404).unwrap_err();
-        let method_not_allowed_err = lapi.get_status(405).unwrap_err();
-        let unsupported_media_err = lapi.get_status(415).unwrap_err();
-        let rate_err = lapi.get_status(429).unwrap_err();
-        let internal_err = lapi.get_status(500).unwrap_err();
-        let bad_g_err = lapi.get_status(502).unwrap_err();
-        let service_err = lapi.get_status(503).unwrap_err();
-        let gateway_t_err = lapi.get_status(504).unwrap_err();
-        assert_matches!(bad_r_err, ClientError::BadRequest);
-        assert_matches!(unauthorized_err, ClientError::Unauthorized);
-        assert_matches!(forbidden_err, ClientError::Forbidden);
-        assert_matches!(not_found_err, ClientError::DataNotFound);
-        assert_matches!(method_not_allowed_err, ClientError::MethodNotAllowed);
-        assert_matches!(unsupported_media_err, ClientError::UnsupportedMediaType);
-        assert_matches!(rate_err, ClientError::RateLimitExceeded { limit: 0 });
-        assert_matches!(internal_err, ClientError::InternalServerError);
-        assert_matches!(bad_g_err, ClientError::BadGateway);
+        let bad_r_err = status_error(400);
+        let unauthorized_err = status_error(401);
+        let forbidden_err = status_error(403);
+        let not_found_err = status_error(404);
+        let method_not_allowed_err = status_error(405);
+        let unsupported_media_err = status_error(415);
+        let rate_err = status_error(429);
+        let internal_err = status_error(500);
+        let bad_g_err = status_error(502);
+        let service_err = status_error(503);
+        let gateway_t_err = status_error(504);
+        assert_matches!(bad_r_err, ClientError::BadRequest { url: None });
+        assert_matches!(unauthorized_err, ClientError::Unauthorized { url: None });
+        assert_matches!(forbidden_err, ClientError::Forbidden { url: None });
+        assert_matches!(not_found_err, ClientError::DataNotFound { url: None });
+        assert_matches!(
+            method_not_allowed_err,
+            ClientError::MethodNotAllowed { url: None }
+        );
+        assert_matches!(
+            unsupported_media_err,
+            ClientError::UnsupportedMediaType { url: None }
+        );
+        assert_matches!(
+            rate_err,
+            ClientError::RateLimitExceeded {
+                retry_after: None,
+                limit_type: None,
+                url: None
+            }
+        );
+        assert_matches!(internal_err, ClientError::InternalServerError { url: None });
+        assert_matches!(bad_g_err, ClientError::BadGateway { url: None });
         assert_matches!(
             service_err,
-            ClientError::ServiceUnavailable { region: Region::NA }
+            ClientError::ServiceUnavailable {
+                region: Region::NA,
+                url: None
+            }
+        );
+        assert_matches!(gateway_t_err, ClientError::GatewayTimeout { url: None })
+    }
+
+    #[test]
+    fn success_codes_are_not_errors() {
+        for code in &[200, 201, 204] {
+            assert!(ClientError::from_status(Region::NA, *code).is_none());
+            assert!(ClientError::check_status(Region::NA, *code).is_ok());
+        }
+    }
+
+    #[test]
+    fn unknown_codes_are_errors() {
+        for code in &[304, 408, 418, 422, 501] {
+            assert_matches!(
+                status_error(*code),
+                ClientError::UnexpectedStatus { status, url: None } if status == *code
+            );
+            assert!(ClientError::check_status(Region::NA, *code).is_err());
+        }
+    }
+
+    #[test]
+    fn reads_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        headers.insert("X-Rate-Limit-Type", HeaderValue::from_static("method"));
+        let url: Uri = "https://na1.api.riotgames.com/lol".parse().unwrap();
+        let err = ClientError::check_response(Region::NA, &url, 429, &headers).unwrap_err();
+        assert_matches!(
+            err,
+            ClientError::RateLimitExceeded {
+                retry_after: Some(ref after),
+                limit_type: Some(ref limit_type),
+                url: Some(_)
+            } if *after == Duration::from_secs(7) && limit_type == "method"
+        );
+        assert!(ClientError::check_response(Region::NA, &url, 200, &headers).is_ok());
+    }
+
+    #[test]
+    fn attaches_url_to_not_found() {
+        let url: Uri = "https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-name/x"
+            .parse()
+            .unwrap();
+        let err =
+            ClientError::check_response(Region::NA, &url, 404, &HeaderMap::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Got 404: Data not found at https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-name/x"
         );
-        assert_matches!(gateway_t_err, ClientError::GatewayTimeout)
+    }
+
+    #[test]
+    fn attaches_url_to_every_status_error() {
+        let url: Uri = "https://na1.api.riotgames.com/lol/status/v4/platform-data"
+            .parse()
+            .unwrap();
+        for code in &[400, 401, 403, 404, 405, 415, 418, 429, 500, 502, 503, 504] {
+            let err = ClientError::check_response(Region::NA, &url, *code, &HeaderMap::new())
+                .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("https://na1.api.riotgames.com/lol/status/v4/platform-data"),
+                "{} has no url",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn converts_hyper_errors() {
+        let client = crate::utils::construct_hyper_client();
+        let err = smol::run(async {
+            // Nothing listens on port 1, so connection is refused
+            client
+                .get("http://127.0.0.1:1".parse().unwrap())
+                .await
+                .unwrap_err()
+        });
+        let err: ClientError = err.into();
+        assert_matches!(err, ClientError::HyperError { .. });
     }
 }
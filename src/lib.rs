@@ -3,8 +3,11 @@
 //! Fast and easy to use wrapper for League of Legends REST API and DDragon static data API.
 //!
 //! Narwhalol bundles both Riot League of Legends and DDragon wrapper clients in itself.
+//! Both of them send requests with the same hyper [`Client`](types/type.Client.html),
+//! so only one HTTP stack is compiled in.
 extern crate hyper;
 pub mod api;
+pub mod cache;
 #[cfg_attr(tarpaulin, skip)]
 pub mod constants;
 pub mod ddragon;
@@ -13,66 +16,78 @@ pub mod ddragon;
 pub mod dto;
 #[allow(missing_docs)]
 pub mod error;
-pub(crate) mod types;
+pub mod rate_limit;
+pub mod types;
 pub(crate) mod utils;
 
 pub use {
-    api::LeagueClient,
+    api::{LeagueClient, LeagueClientBuilder, MasterySort, MatchIdQuery, RetryPolicy},
+    cache::{Cache, CacheBackend, CacheStats, Cached},
     constants::{LanguageCode, RankedQueue, Region},
     dto::api::*,
     dto::ddragon::*,
+    dto::tft::*,
+    rate_limit::{RateLimit, RateLimiter},
 };
 
 #[cfg(test)]
 mod tests {
-    use crate::{Summoner, LeagueClient, Region};
-    use std::time::Duration;
+    use crate::utils::mock::{MockResponse, MockServer};
+    use crate::{LeagueClient, LeagueClientBuilder, Region};
+
+    const SUMMONER_JSON: &str = r#"{
+        "profileIconId": 1,
+        "name": "Vetro",
+        "puuid": "test-puuid",
+        "summonerLevel": 30,
+        "revisionDate": 1589999999000,
+        "id": "test-id",
+        "accountId": "test-account-id"
+    }"#;
+
+    fn mock_client(server: &MockServer) -> LeagueClient {
+        LeagueClientBuilder::new(Region::RU)
+            .api_key("RGAPI-00000000-0000-0000-0000-000000000000")
+            .host(server.url())
+            .build()
+            .unwrap()
+    }
 
     #[test]
     #[cfg(feature = "async_std_rt")]
     fn ensure_different_runtimes_work_with_lib() {
         pretty_env_logger::try_init().unwrap_or(());
-        let lapi = LeagueClient::new(Region::RU).unwrap();
-        let sum = async_std::task::block_on(async {
-            lapi.get_summoner_by_name("Vetro").await.unwrap()
-        });
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        let lapi = mock_client(&server);
+        let sum =
+            async_std::task::block_on(async { lapi.get_summoner_by_name("Vetro").await.unwrap() });
 
         log::debug!("summoner from async_std: {:?}", sum);
         assert_eq!("Vetro", &sum.name);
-
-        std::thread::sleep(Duration::from_secs(10));
     }
 
     #[test]
     #[cfg(feature = "tokio_rt")]
     fn ensure_different_runtimes_work_with_lib() {
         pretty_env_logger::try_init().unwrap_or(());
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        let lapi = mock_client(&server);
         let mut rt = tokio::runtime::Runtime::new().unwrap();
-        println!("in tokio rt");
+        let sum = rt.block_on(async { lapi.get_summoner_by_name("Vetro").await.unwrap() });
 
-        let sum = rt.block_on(async {
-            let sum = get_summoner_vetro().await;
-            sum
-        });
-
-        println!("summoner from tokio: {:?}", sum);
+        log::debug!("summoner from tokio: {:?}", sum);
         assert_eq!("Vetro", &sum.name);
-
-        std::thread::sleep(Duration::from_secs(10));
     }
 
     #[test]
     #[cfg(feature = "smol_rt")]
     fn ensure_different_runtimes_work_with_lib() {
         pretty_env_logger::try_init().unwrap_or(());
-        let sum = smol::run(async {
-            let sum = get_summoner_vetro().await;
-            sum
-        });
+        let server = MockServer::start(vec![MockResponse::new(200, SUMMONER_JSON)]);
+        let lapi = mock_client(&server);
+        let sum = smol::run(async { lapi.get_summoner_by_name("Vetro").await.unwrap() });
 
         log::debug!("summoner from smol_rt: {:?}", sum);
         assert_eq!("Vetro", &sum.name);
-
-        std::thread::sleep(Duration::from_secs(10));
     }
 }
@@ -0,0 +1,406 @@
+//! Client side rate limiting of League API requests.
+//!
+//! See [`RateLimiter`] for more information.
+//!
+//! [`RateLimiter`]: struct.RateLimiter.html
+use futures_timer::Delay;
+use hyper::{HeaderMap, Uri};
+use log::debug;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Keeps outgoing requests under Riot application rate limits.
+///
+/// Every limit is a bucket of `limit` requests which is refilled once its period passes.
+/// Limits passed to [`new`] are used until the first response arrives, after that they are
+/// synced with `X-App-Rate-Limit` and `X-App-Rate-Limit-Count` headers returned by Riot.
+///
+/// Riot also limits every method (endpoint) separately. Those limits are only known from
+/// `X-Method-Rate-Limit` headers, so each method gets its own buckets after its first response,
+/// e.g. summoner lookups and match lookups never share a counter.
+///
+/// Clones share the same buckets, so one limiter can be given to several clients using the same api key.
+///
+/// [`new`]: #method.new
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<Vec<Bucket>>>,
+    /// Buckets of every method seen so far, keyed by [`method_key`]
+    ///
+    /// [`method_key`]: fn.method_key.html
+    methods: Arc<Mutex<HashMap<String, Vec<Bucket>>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: u32,
+    period: Duration,
+    count: u32,
+    started: Instant,
+}
+
+impl Bucket {
+    fn new(limit: u32, period: Duration) -> Self {
+        Bucket {
+            limit,
+            period,
+            count: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Time left until the bucket is refilled, `None` if request can be sent right away
+    fn wait_time(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.duration_since(self.started);
+        if elapsed >= self.period {
+            self.count = 0;
+            self.started = now;
+            return None;
+        }
+        if self.count < self.limit {
+            None
+        } else {
+            Some(self.period - elapsed)
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Creates limiter with initial `(requests, period)` limits
+    pub fn new(limits: &[(u32, Duration)]) -> Self {
+        let buckets = limits
+            .iter()
+            .map(|&(limit, period)| Bucket::new(limit, period))
+            .collect();
+        RateLimiter {
+            buckets: Arc::new(Mutex::new(buckets)),
+            methods: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Limits of a development api key: 20 requests every second and 100 requests every 2 minutes
+    pub fn development_key() -> Self {
+        RateLimiter::new(&[
+            (20, Duration::from_secs(1)),
+            (100, Duration::from_secs(120)),
+        ])
+    }
+
+    /// Waits until request to the method can be sent without exceeding app and method limits
+    /// and reserves it. Dropping the future while it waits reserves nothing.
+    pub(crate) async fn acquire(&self, method: &str) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock();
+                let mut methods = self.methods.lock();
+                let mut method_buckets = methods.get_mut(method);
+                let now = Instant::now();
+                let wait = buckets
+                    .iter_mut()
+                    .chain(method_buckets.iter_mut().flat_map(|b| b.iter_mut()))
+                    .filter_map(|b| b.wait_time(now))
+                    .max();
+                if wait.is_none() {
+                    buckets
+                        .iter_mut()
+                        .chain(method_buckets.iter_mut().flat_map(|b| b.iter_mut()))
+                        .for_each(|b| b.count += 1);
+                }
+                wait
+            };
+            match wait {
+                Some(wait) => {
+                    debug!("Rate limit of {} reached, waiting for {:?}", method, wait);
+                    Delay::new(wait).await
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Syncs app limits and limits of the method with the ones reported by Riot
+    pub(crate) fn update(&self, method: &str, headers: &HeaderMap) {
+        if let Some(limits) = header_limits(headers, "X-App-Rate-Limit") {
+            let counts = header_limits(headers, "X-App-Rate-Limit-Count").unwrap_or_default();
+            sync_buckets(&mut self.buckets.lock(), limits, &counts);
+        }
+        if let Some(limits) = header_limits(headers, "X-Method-Rate-Limit") {
+            let counts = header_limits(headers, "X-Method-Rate-Limit-Count").unwrap_or_default();
+            let mut methods = self.methods.lock();
+            sync_buckets(
+                methods.entry(method.to_owned()).or_default(),
+                limits,
+                &counts,
+            );
+        }
+    }
+}
+
+fn sync_buckets(
+    buckets: &mut Vec<Bucket>,
+    limits: Vec<(u32, Duration)>,
+    counts: &[(u32, Duration)],
+) {
+    buckets.retain(|b| limits.iter().any(|&(_, period)| period == b.period));
+    for (limit, period) in limits {
+        let count = counts
+            .iter()
+            .find(|&&(_, p)| p == period)
+            .map_or(0, |&(count, _)| count);
+        match buckets.iter_mut().find(|b| b.period == period) {
+            Some(bucket) => {
+                bucket.limit = limit;
+                bucket.count = bucket.count.max(count);
+            }
+            None => {
+                let mut bucket = Bucket::new(limit, period);
+                bucket.count = count;
+                buckets.push(bucket);
+            }
+        }
+    }
+}
+
+/// Identifies the method (endpoint) a request belongs to for method rate limits,
+/// e.g. `na1.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/{}`.
+///
+/// Riot counts method limits per region, so the host is kept. Path segments that look like
+/// user provided values (ids, names, queues, tiers) are replaced with `{}`, query is dropped.
+pub(crate) fn method_key(url: &Uri) -> String {
+    let mut key = url
+        .authority()
+        .map_or("", |a| a.as_str())
+        .to_ascii_lowercase();
+    let mut after_by = false;
+    for segment in url.path().split('/').filter(|s| !s.is_empty()) {
+        key.push('/');
+        let is_static = !after_by
+            && (segment.bytes().all(|b| b.is_ascii_lowercase() || b == b'-')
+                || is_api_version(segment));
+        if is_static {
+            key.push_str(segment);
+        } else {
+            key.push_str("{}");
+        }
+        // Segment after `by-puuid`, `by-name` and alike is always a value
+        after_by = segment.starts_with("by-");
+    }
+    key
+}
+
+/// Whether the segment is api version, e.g. `v4`
+fn is_api_version(segment: &str) -> bool {
+    segment.starts_with('v')
+        && segment.len() > 1
+        && segment[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Rate limit budget reported by Riot along with a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Windows of application rate limit, shared by all requests made with the api key
+    pub app: Vec<RateLimitWindow>,
+    /// Windows of method rate limit, specific to the requested endpoint
+    pub method: Vec<RateLimitWindow>,
+}
+
+/// One `limit:seconds` window of a rate limit along with number of requests already made in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitWindow {
+    /// Maximum number of requests in the window
+    pub limit: u32,
+    /// Number of requests made in current window
+    pub count: u32,
+    /// Length of the window
+    pub period: Duration,
+}
+
+impl RateLimitWindow {
+    /// Number of requests that can still be made in current window
+    pub fn remaining(&self) -> u32 {
+        self.limit.saturating_sub(self.count)
+    }
+}
+
+impl RateLimit {
+    /// Parses `X-App-Rate-Limit`, `X-Method-Rate-Limit` and their `-Count` headers,
+    /// returns `None` if response has none of them.
+    pub fn from_headers(headers: &HeaderMap) -> Option<RateLimit> {
+        let app = header_windows(headers, "X-App-Rate-Limit");
+        let method = header_windows(headers, "X-Method-Rate-Limit");
+        if app.is_none() && method.is_none() {
+            return None;
+        }
+        Some(RateLimit {
+            app: app.unwrap_or_default(),
+            method: method.unwrap_or_default(),
+        })
+    }
+}
+
+fn header_windows(headers: &HeaderMap, name: &str) -> Option<Vec<RateLimitWindow>> {
+    let limits = header_limits(headers, name)?;
+    let counts = header_limits(headers, &format!("{}-Count", name)).unwrap_or_default();
+    let windows = limits
+        .into_iter()
+        .map(|(limit, period)| RateLimitWindow {
+            limit,
+            count: counts
+                .iter()
+                .find(|&&(_, p)| p == period)
+                .map_or(0, |&(count, _)| count),
+            period,
+        })
+        .collect();
+    Some(windows)
+}
+
+fn header_limits(headers: &HeaderMap, name: &str) -> Option<Vec<(u32, Duration)>> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(parse_limits)
+}
+
+/// Parses `limit:seconds,limit:seconds` header value
+pub(crate) fn parse_limits(value: &str) -> Vec<(u32, Duration)> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let mut parts = pair.trim().split(':');
+            let limit = parts.next()?.parse().ok()?;
+            let secs = parts.next()?.parse().ok()?;
+            Some((limit, Duration::from_secs(secs)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{method_key, parse_limits, RateLimit, RateLimitWindow, RateLimiter};
+    use hyper::header::{HeaderMap, HeaderValue};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn parses_multi_window_limits() {
+        assert_eq!(
+            parse_limits("20:1,100:120"),
+            vec![
+                (20, Duration::from_secs(1)),
+                (100, Duration::from_secs(120))
+            ]
+        );
+        assert!(parse_limits("garbage").is_empty());
+    }
+
+    #[test]
+    fn waits_when_bucket_is_empty() {
+        let limiter = RateLimiter::new(&[(2, Duration::from_millis(300))]);
+        smol::run(async {
+            let now = Instant::now();
+            for _ in 0..3 {
+                limiter.acquire("method").await;
+            }
+            assert!(now.elapsed() >= Duration::from_millis(250));
+        })
+    }
+
+    #[test]
+    fn syncs_limits_with_headers() {
+        let limiter = RateLimiter::development_key();
+        let mut headers = HeaderMap::new();
+        headers.insert("X-App-Rate-Limit", HeaderValue::from_static("5:10"));
+        headers.insert("X-App-Rate-Limit-Count", HeaderValue::from_static("5:10"));
+        limiter.update("method", &headers);
+        let buckets = limiter.buckets.lock();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].limit, 5);
+        assert_eq!(buckets[0].count, 5);
+    }
+
+    #[test]
+    fn tracks_method_limits_separately() {
+        let limiter = RateLimiter::new(&[]);
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Method-Rate-Limit", HeaderValue::from_static("1:10"));
+        headers.insert(
+            "X-Method-Rate-Limit-Count",
+            HeaderValue::from_static("1:10"),
+        );
+        limiter.update("summoner", &headers);
+        smol::run(async {
+            let now = Instant::now();
+            limiter.acquire("match").await;
+            limiter.acquire("match").await;
+            assert!(now.elapsed() < Duration::from_millis(100));
+        });
+        let mut methods = limiter.methods.lock();
+        assert!(!methods.contains_key("match"));
+        let summoner = methods.get_mut("summoner").unwrap();
+        assert_eq!((summoner[0].limit, summoner[0].count), (1, 1));
+        assert!(summoner[0].wait_time(Instant::now()).is_some());
+    }
+
+    #[test]
+    fn normalizes_method_keys() {
+        let key = |url: &str| method_key(&url.parse().unwrap());
+        assert_eq!(
+            key("https://NA1.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/abc-DEF_123"),
+            "na1.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/{}"
+        );
+        assert_eq!(
+            key("https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-name/vetro"),
+            key("https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-name/santorin")
+        );
+        assert_eq!(
+            key("https://americas.api.riotgames.com/lol/match/v5/matches/NA1_3456/timeline"),
+            "americas.api.riotgames.com/lol/match/v5/matches/{}/timeline"
+        );
+        assert_eq!(
+            key(
+                "https://na1.api.riotgames.com/lol/league/v4/entries/RANKED_SOLO_5x5/GOLD/I?page=2"
+            ),
+            "na1.api.riotgames.com/lol/league/v4/entries/{}/{}/{}"
+        );
+        assert_ne!(
+            key("https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/abc1"),
+            key("https://na1.api.riotgames.com/lol/summoner/v4/summoners/by-account/abc1")
+        );
+    }
+
+    #[test]
+    fn parses_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-App-Rate-Limit", HeaderValue::from_static("20:1,100:120"));
+        headers.insert(
+            "X-App-Rate-Limit-Count",
+            HeaderValue::from_static("1:1,42:120"),
+        );
+        headers.insert("X-Method-Rate-Limit", HeaderValue::from_static("2000:60"));
+        headers.insert(
+            "X-Method-Rate-Limit-Count",
+            HeaderValue::from_static("3:60"),
+        );
+        let rate_limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(
+            rate_limit.app,
+            vec![
+                RateLimitWindow {
+                    limit: 20,
+                    count: 1,
+                    period: Duration::from_secs(1)
+                },
+                RateLimitWindow {
+                    limit: 100,
+                    count: 42,
+                    period: Duration::from_secs(120)
+                },
+            ]
+        );
+        assert_eq!(rate_limit.app[1].remaining(), 58);
+        assert_eq!(rate_limit.method[0].remaining(), 1997);
+        assert!(RateLimit::from_headers(&HeaderMap::new()).is_none());
+    }
+}
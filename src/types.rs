@@ -1,3 +1,15 @@
+//! Hyper client types of the runtime selected with `smol_rt`, `async_std_rt` or `tokio_rt` feature.
+//!
+//! These are only needed to pass a preconfigured client to [`LeagueClientBuilder::http_client`].
+//!
+//! With `smol_rt` and `async_std_rt` all connections are tunneled through HTTP proxy
+//! from `HTTPS_PROXY` or `HTTP_PROXY` environment variable if it is set,
+//! see [`CompatConnector::with_proxy`] to configure it explicitly. `tokio_rt` does not support
+//! HTTP proxy yet and always connects directly, a warning is logged if one of these variables is set.
+//!
+//! [`LeagueClientBuilder::http_client`]: ../api/struct.LeagueClientBuilder.html#method.http_client
+//! [`CompatConnector::with_proxy`]: compat/struct.CompatConnector.html#method.with_proxy
+
 use crate::error::{ClientError, IOError, NativeTLSError, UnsupportedScheme, UrlNotParsed};
 use async_native_tls::TlsStream;
 use futures::future::BoxFuture;
@@ -13,26 +25,108 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-pub(crate) type Client = Arc<HttpClient<compat::CompatConnector>>;
-pub(crate) type Cache<K = Uri, V = String> = Arc<Mutex<HashMap<K, V>>>;
+/// Hyper client shared by [`LeagueClient`] and [`DDragonClient`].
+///
+/// [`LeagueClient`]: ../api/struct.LeagueClient.html
+/// [`DDragonClient`]: ../ddragon/struct.DDragonClient.html
+pub type Client = Arc<HttpClient<compat::CompatConnector>>;
 
-#[cfg(feature = "smol_rt")]
-pub(crate) mod compat {
-    use std::future::Future;
-    use smol::{Task, Async};
+/// Environment variables HTTP proxy is taken from, in order of preference
+const PROXY_VARS: [&str; 4] = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"];
+
+/// HTTP proxy support of `smol_rt` and `async_std_rt` connectors
+#[cfg(not(feature = "tokio_rt"))]
+mod proxy {
+    use crate::error::{ClientError, IOError, ProxyConnect};
+    use futures::prelude::*;
     use hyper::Uri;
-    use crate::error::{ClientError, UrlNotParsed, IOError, NativeTLSError};
+    use snafu::{ensure, ResultExt};
+    use std::env;
+
+    /// Proxy from `HTTPS_PROXY` or `HTTP_PROXY` environment variable (or their lowercase versions)
+    pub(crate) fn proxy_from_env() -> Option<Uri> {
+        super::PROXY_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse().ok())
+    }
+
+    /// Whether requests to the host should not go through proxy,
+    /// either because it is local or listed in `NO_PROXY` environment variable
+    pub(crate) fn bypasses_proxy(host: &str) -> bool {
+        if host == "localhost" || host == "127.0.0.1" {
+            return true;
+        }
+        env::var("NO_PROXY")
+            .or_else(|_| env::var("no_proxy"))
+            .map(|list| no_proxy_matches(&list, host))
+            .unwrap_or(false)
+    }
+
+    /// Whether comma separated `NO_PROXY` list matches the host or one of its parent domains
+    pub(crate) fn no_proxy_matches(list: &str, host: &str) -> bool {
+        list.split(',')
+            .map(|entry| entry.trim().trim_start_matches('.'))
+            .filter(|entry| !entry.is_empty())
+            .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)))
+    }
+
+    /// Asks HTTP proxy to open a tunnel to `host:port` with `CONNECT` method
+    pub(crate) async fn connect_tunnel<S>(
+        stream: &mut S,
+        host: &str,
+        port: u16,
+    ) -> Result<(), ClientError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let request = format!(
+            "CONNECT {0}:{1} HTTP/1.1\r\nHost: {0}:{1}\r\n\r\n",
+            host, port
+        );
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .context(IOError)?;
+        // Read byte by byte so nothing after the headers is consumed from the tunnel
+        let mut response = Vec::new();
+        let mut byte = [0_u8; 1];
+        while !response.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte).await.context(IOError)? == 0 {
+                break;
+            }
+            response.push(byte[0]);
+        }
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        ensure!(
+            status_line.split_whitespace().nth(1) == Some("200"),
+            ProxyConnect {
+                response: status_line.to_owned()
+            }
+        );
+        Ok(())
+    }
+}
+
+#[cfg(feature = "smol_rt")]
+pub mod compat {
+    use crate::error::{ClientError, IOError, NativeTLSError, UrlNotParsed};
+    use async_native_tls::TlsStream;
     use futures::future::BoxFuture;
-    use std::task::{Context, Poll};
+    use futures::{AsyncRead, AsyncWrite};
+    use hyper::Uri;
+    use smol::{Async, Task};
     use snafu::{OptionExt, ResultExt};
-    use std::net::{TcpStream, Shutdown};
-    use async_native_tls::TlsStream;
-    use std::pin::Pin;
+    use std::future::Future;
     use std::io;
-    use futures::{AsyncWrite, AsyncRead};
+    use std::net::{Shutdown, TcpStream};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
 
     #[derive(Clone)]
-    pub(crate) struct CompatExecutor;
+    pub struct CompatExecutor;
 
     impl<F: Future + Send + 'static> hyper::rt::Executor<F> for CompatExecutor {
         fn execute(&self, fut: F) {
@@ -40,13 +134,30 @@ pub(crate) mod compat {
         }
     }
 
-    /// Connects to URLs.
+    /// Connects to URLs, through HTTP proxy if one is configured.
     #[derive(Clone)]
-    pub(crate) struct CompatConnector;
+    pub struct CompatConnector {
+        proxy: Option<Uri>,
+    }
 
     impl CompatConnector {
-        pub(crate) fn new() -> Self {
-            Self
+        /// Creates connector using proxy from `HTTPS_PROXY` or `HTTP_PROXY` environment variable,
+        /// hosts listed in `NO_PROXY` are connected directly.
+        pub fn new() -> Self {
+            CompatConnector {
+                proxy: super::proxy::proxy_from_env(),
+            }
+        }
+
+        /// Creates connector tunneling all connections through given HTTP proxy.
+        pub fn with_proxy(proxy: Uri) -> Self {
+            CompatConnector { proxy: Some(proxy) }
+        }
+    }
+
+    impl Default for CompatConnector {
+        fn default() -> Self {
+            Self::new()
         }
     }
 
@@ -60,36 +171,50 @@ pub(crate) mod compat {
         }
 
         fn call(&mut self, uri: Uri) -> Self::Future {
+            let proxy = self
+                .proxy
+                .clone()
+                .filter(|_| !uri.host().map_or(true, super::proxy::bypasses_proxy));
             Box::pin(async move {
                 let host = uri.host().context(UrlNotParsed)?;
-
-                match uri.scheme_str() {
-                    Some("http") => {
-                        let addr = format!("{}:{}", uri.host().unwrap(), uri.port_u16().unwrap_or(80));
-                        let stream = Async::<TcpStream>::connect(addr).await.context(IOError)?;
-                        Ok(CompatStream::Plain(stream))
-                    }
-                    Some("https") => {
-                        // In case of HTTPS, establish a secure TLS connection first.
-                        let addr = format!("{}:{}", uri.host().unwrap(), uri.port_u16().unwrap_or(443));
-                        let stream = Async::<TcpStream>::connect(addr).await.context(IOError)?;
-                        let stream = async_native_tls::connect(host, stream)
-                            .await
-                            .context(NativeTLSError)?;
-                        Ok(CompatStream::Tls(stream))
-                    }
+                let tls = match uri.scheme_str() {
+                    Some("http") => false,
+                    Some("https") => true,
                     scheme => {
                         return Err(ClientError::UnsupportedScheme {
                             scheme: scheme.map(|s| s.to_owned()),
                         })
                     }
+                };
+                let port = uri.port_u16().unwrap_or(if tls { 443 } else { 80 });
+                let stream = match &proxy {
+                    Some(proxy) => {
+                        let proxy_host = proxy.host().context(UrlNotParsed)?;
+                        let addr = format!("{}:{}", proxy_host, proxy.port_u16().unwrap_or(80));
+                        let mut stream =
+                            Async::<TcpStream>::connect(addr).await.context(IOError)?;
+                        super::proxy::connect_tunnel(&mut stream, host, port).await?;
+                        stream
+                    }
+                    None => Async::<TcpStream>::connect(format!("{}:{}", host, port))
+                        .await
+                        .context(IOError)?,
+                };
+                if tls {
+                    // In case of HTTPS, establish a secure TLS connection first.
+                    let stream = async_native_tls::connect(host, stream)
+                        .await
+                        .context(NativeTLSError)?;
+                    Ok(CompatStream::Tls(stream))
+                } else {
+                    Ok(CompatStream::Plain(stream))
                 }
             })
         }
     }
 
     /// A TCP or TCP+TLS connection.
-    pub(crate) enum CompatStream {
+    pub enum CompatStream {
         /// A plain TCP connection.
         Plain(Async<TcpStream>),
 
@@ -148,16 +273,22 @@ pub(crate) mod compat {
 }
 
 #[cfg(feature = "async_std_rt")]
-pub(crate) mod compat {
+pub mod compat {
+    use crate::error::{ClientError, IOError, NativeTLSError, UrlNotParsed};
+    use async_native_tls::TlsStream;
     use async_std::{io, net::TcpStream, task};
+    use futures::future::BoxFuture;
     use futures::io::AsyncWriteExt;
+    use futures::{AsyncRead, AsyncWrite};
     use hyper::{
         body::HttpBody as _,
         client::connect::{Connected, Connection},
         rt::Executor,
-        Body, Client, Uri
+        Body, Client, Uri,
     };
     use pin_project::pin_project;
+    use snafu::{OptionExt, ResultExt};
+    use std::net::Shutdown;
     use std::{
         error::Error,
         future::Future,
@@ -166,35 +297,47 @@ pub(crate) mod compat {
         task::{Context, Poll},
     };
     use tokio::io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite};
-    use async_native_tls::TlsStream;
-    use futures::{AsyncWrite, AsyncRead};
-    use std::net::Shutdown;
-    use crate::error::{ClientError, UrlNotParsed, NativeTLSError, IOError, };
-    use snafu::{ResultExt, OptionExt};
-    use futures::future::BoxFuture;
 
-    pub(crate) struct CompatExecutor;
+    pub struct CompatExecutor;
 
     impl<Fut> Executor<Fut> for CompatExecutor
-        where
-            Fut: Future + Send + 'static,
-            Fut::Output: Send + 'static,
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
     {
         fn execute(&self, fut: Fut) {
             task::spawn(async move { fut.await });
         }
     }
 
+    /// Connects to URLs, through HTTP proxy if one is configured.
     #[derive(Clone)]
-    pub(crate) struct CompatConnector;
+    pub struct CompatConnector {
+        proxy: Option<Uri>,
+    }
 
     impl CompatConnector {
-        pub(crate) fn new() -> Self {
-            Self
+        /// Creates connector using proxy from `HTTPS_PROXY` or `HTTP_PROXY` environment variable,
+        /// hosts listed in `NO_PROXY` are connected directly.
+        pub fn new() -> Self {
+            CompatConnector {
+                proxy: super::proxy::proxy_from_env(),
+            }
+        }
+
+        /// Creates connector tunneling all connections through given HTTP proxy.
+        pub fn with_proxy(proxy: Uri) -> Self {
+            CompatConnector { proxy: Some(proxy) }
+        }
+    }
+
+    impl Default for CompatConnector {
+        fn default() -> Self {
+            Self::new()
         }
     }
 
-    pub(crate) enum CompatStream {
+    pub enum CompatStream {
         /// A plain TCP connection.
         Plain(TcpStream),
 
@@ -262,29 +405,42 @@ pub(crate) mod compat {
         }
 
         fn call(&mut self, uri: Uri) -> Self::Future {
+            let proxy = self
+                .proxy
+                .clone()
+                .filter(|_| !uri.host().map_or(true, super::proxy::bypasses_proxy));
             Box::pin(async move {
                 let host = uri.host().context(UrlNotParsed)?;
-
-                match uri.scheme_str() {
-                    Some("http") => {
-                        let addr = format!("{}:{}", uri.host().unwrap(), uri.port_u16().unwrap_or(80));
-                        let stream = TcpStream::connect(addr).await.context(IOError)?;
-                        Ok(CompatStream::Plain(stream))
-                    }
-                    Some("https") => {
-                        // In case of HTTPS, establish a secure TLS connection first.
-                        let addr = format!("{}:{}", uri.host().unwrap(), uri.port_u16().unwrap_or(443));
-                        let stream = TcpStream::connect(addr).await.context(IOError)?;
-                        let stream = async_native_tls::connect(host, stream)
-                            .await
-                            .context(NativeTLSError)?;
-                        Ok(CompatStream::Tls(stream))
-                    }
+                let tls = match uri.scheme_str() {
+                    Some("http") => false,
+                    Some("https") => true,
                     scheme => {
                         return Err(ClientError::UnsupportedScheme {
                             scheme: scheme.map(|s| s.to_owned()),
                         })
                     }
+                };
+                let port = uri.port_u16().unwrap_or(if tls { 443 } else { 80 });
+                let stream = match &proxy {
+                    Some(proxy) => {
+                        let proxy_host = proxy.host().context(UrlNotParsed)?;
+                        let addr = format!("{}:{}", proxy_host, proxy.port_u16().unwrap_or(80));
+                        let mut stream = TcpStream::connect(addr).await.context(IOError)?;
+                        super::proxy::connect_tunnel(&mut stream, host, port).await?;
+                        stream
+                    }
+                    None => TcpStream::connect(format!("{}:{}", host, port))
+                        .await
+                        .context(IOError)?,
+                };
+                if tls {
+                    // In case of HTTPS, establish a secure TLS connection first.
+                    let stream = async_native_tls::connect(host, stream)
+                        .await
+                        .context(NativeTLSError)?;
+                    Ok(CompatStream::Tls(stream))
+                } else {
+                    Ok(CompatStream::Plain(stream))
                 }
             })
         }
@@ -292,9 +448,71 @@ pub(crate) mod compat {
 }
 
 #[cfg(feature = "tokio_rt")]
-pub(crate) mod compat {
-    use hyper_tls::HttpsConnector;
+pub mod compat {
     use hyper::client::HttpConnector;
+    use hyper_tls::HttpsConnector;
+    use log::warn;
+    use std::env;
 
+    /// Connects to URLs directly, HTTP proxy is not supported with `tokio_rt`.
     pub type CompatConnector = HttpsConnector<HttpConnector>;
-}
\ No newline at end of file
+
+    /// Logs a warning if HTTP proxy is configured in environment, as it is ignored with `tokio_rt`
+    pub(crate) fn warn_ignored_proxy() {
+        let proxy_var = super::PROXY_VARS
+            .iter()
+            .find(|var| env::var_os(var).map_or(false, |value| !value.is_empty()));
+        if let Some(var) = proxy_var {
+            warn!(
+                "{} is set, but HTTP proxy is not supported with tokio_rt, connecting directly",
+                var
+            );
+        }
+    }
+}
+#[cfg(all(test, feature = "smol_rt"))]
+mod tests {
+    use super::proxy::{bypasses_proxy, connect_tunnel, no_proxy_matches};
+    use crate::error::ClientError;
+    use crate::utils::mock::{MockResponse, MockServer};
+    use smol::Async;
+    use std::net::TcpStream;
+
+    #[test]
+    fn opens_proxy_tunnel() {
+        let server =
+            MockServer::start(vec![MockResponse::new(200, ""), MockResponse::new(407, "")]);
+        let addr = server.url().trim_start_matches("http://").to_owned();
+        smol::run(async {
+            let mut stream = Async::<TcpStream>::connect(addr.clone()).await.unwrap();
+            connect_tunnel(&mut stream, "ddragon.leagueoflegends.com", 443)
+                .await
+                .unwrap();
+            assert_eq!(server.requests()[0].path, "ddragon.leagueoflegends.com:443");
+
+            let mut stream = Async::<TcpStream>::connect(addr).await.unwrap();
+            let err = connect_tunnel(&mut stream, "ddragon.leagueoflegends.com", 443)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ClientError::ProxyConnect { .. }));
+        })
+    }
+
+    #[test]
+    fn never_proxies_local_hosts() {
+        assert!(bypasses_proxy("localhost"));
+        assert!(bypasses_proxy("127.0.0.1"));
+    }
+
+    #[test]
+    fn matches_no_proxy_on_domain_boundary() {
+        assert!(no_proxy_matches("example.com", "example.com"));
+        assert!(no_proxy_matches("example.com", "api.example.com"));
+        assert!(no_proxy_matches(" .example.com", "api.example.com"));
+        assert!(no_proxy_matches("other.org, example.com", "example.com"));
+        assert!(no_proxy_matches("*", "riotgames.com"));
+        assert!(!no_proxy_matches("example.com", "notexample.com"));
+        assert!(!no_proxy_matches("example.com", "example.com.evil.org"));
+        assert!(!no_proxy_matches("", "example.com"));
+    }
+}
@@ -1,20 +1,29 @@
+use crate::cache::Cache;
 use crate::error::{ClientError, HyperError};
-use crate::types::{Cache, Client};
+use crate::types::compat;
+use crate::types::Client;
 use futures::prelude::*;
 use hyper::header::HeaderValue;
 use hyper::{Body, Client as HttpClient, Request, Response, Uri};
 use log::debug;
-use crate::types::compat;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 
 use serde::de::DeserializeOwned;
 
 use async_trait::async_trait;
 
 use crate::error::*;
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 use std::fmt::Debug;
 use std::sync::Arc;
 
+/// Characters that must be escaped in a single url path segment (everything except RFC 3986 unreserved).
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
 #[async_trait]
 pub(crate) trait CachedClient {
     async fn cached_resp<T: Debug + DeserializeOwned + Send>(
@@ -23,15 +32,23 @@ pub(crate) trait CachedClient {
     ) -> Result<T, ClientError>;
 }
 
-pub(crate) async fn get_latest_ddragon_version(client: Client) -> Result<String, ClientError> {
-    let resp = client
-        .get(
-            "https://ddragon.leagueoflegends.com/api/versions.json"
-                .parse()
-                .unwrap(),
-        )
-        .await
-        .context(HyperError)?;
+pub(crate) async fn get_latest_ddragon_version(
+    client: Client,
+    host: &str,
+) -> Result<String, ClientError> {
+    let versions = get_ddragon_versions(client, host).await?;
+    versions.into_iter().next().context(NoDDragonVersions {
+        host: host.to_owned(),
+    })
+}
+
+/// Fetches all DDragon versions from the host (e.g. `https://ddragon.leagueoflegends.com`), latest first
+pub(crate) async fn get_ddragon_versions(
+    client: Client,
+    host: &str,
+) -> Result<Vec<String>, ClientError> {
+    let url: Uri = parse_url(&format!("{}/api/versions.json", host))?;
+    let resp = client.get(url.clone()).await.context(HyperError)?;
     let body = resp
         .into_body()
         .try_fold(Vec::new(), |mut body, chunk| async move {
@@ -41,9 +58,23 @@ pub(crate) async fn get_latest_ddragon_version(client: Client) -> Result<String,
         .await
         .context(HyperError)?;
     let string_resp = String::from_utf8(body).context(FromUTF8Error)?;
-    let mut versions: Vec<String> = serde_json::from_str(&string_resp).unwrap();
-    let version = versions.remove(0);
-    Ok(version)
+    let versions: Vec<String> = serde_json::from_str(&string_resp).context(Deserialization {
+        url: url.to_string(),
+    })?;
+    Ok(versions)
+}
+
+/// Parses url built from user provided values, returning [`UrlNotParsed`] instead of panicking
+/// on characters that are not allowed in urls (e.g. a newline in an id).
+///
+/// [`UrlNotParsed`]: ../error/enum.ClientError.html#variant.UrlNotParsed
+pub(crate) fn parse_url(url: &str) -> Result<Uri, ClientError> {
+    url.parse().ok().context(UrlNotParsed)
+}
+
+/// Percent-encodes user provided value (e.g. summoner name) so it can be used as a url path segment
+pub(crate) fn encode_path_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
 }
 
 /// Helper function that constructs an https hyper client
@@ -53,9 +84,180 @@ pub(crate) fn construct_hyper_client() -> Client {
         #[cfg(not(feature = "tokio_rt"))]
         () => builder.executor(compat::CompatExecutor),
         #[cfg(feature = "tokio_rt")]
-        () => ()
+        () => compat::warn_ignored_proxy(),
     };
-    let cli = builder
-        .build::<_, Body>(compat::CompatConnector::new());
+    let cli = builder.build::<_, Body>(compat::CompatConnector::new());
     Arc::new(cli)
 }
+
+/// Minimal http server for tests that should not hit real Riot servers.
+#[cfg(test)]
+pub(crate) mod mock {
+    use parking_lot::Mutex;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Canned response of [`MockServer`]
+    pub(crate) struct MockResponse {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: String,
+        delay: Option<Duration>,
+    }
+
+    impl MockResponse {
+        pub(crate) fn new(status: u16, body: &str) -> Self {
+            MockResponse {
+                status,
+                headers: Vec::new(),
+                body: body.to_owned(),
+                delay: None,
+            }
+        }
+
+        pub(crate) fn header(mut self, name: &str, value: &str) -> Self {
+            self.headers.push((name.to_owned(), value.to_owned()));
+            self
+        }
+
+        /// Waits before answering, to simulate slow server
+        pub(crate) fn delay(mut self, delay: Duration) -> Self {
+            self.delay = Some(delay);
+            self
+        }
+    }
+
+    /// Request received by [`MockServer`], header names are lowercased
+    #[derive(Debug, Clone)]
+    pub(crate) struct MockRequest {
+        pub(crate) path: String,
+        pub(crate) headers: Vec<(String, String)>,
+    }
+
+    impl MockRequest {
+        pub(crate) fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.as_str())
+        }
+    }
+
+    /// Answers requests with given responses in order, repeating the last one.
+    pub(crate) struct MockServer {
+        url: String,
+        requests: Arc<Mutex<Vec<MockRequest>>>,
+        peak_concurrency: Arc<AtomicUsize>,
+    }
+
+    impl MockServer {
+        pub(crate) fn start(responses: Vec<MockResponse>) -> MockServer {
+            assert!(!responses.is_empty());
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = requests.clone();
+            let responses = Arc::new(responses);
+            let served = Arc::new(AtomicUsize::new(0));
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            let peak_concurrency = Arc::new(AtomicUsize::new(0));
+            let peak = peak_concurrency.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = match stream {
+                        Ok(stream) => stream,
+                        Err(_) => break,
+                    };
+                    let recorded = recorded.clone();
+                    let responses = responses.clone();
+                    let served = served.clone();
+                    let in_flight = in_flight.clone();
+                    let peak = peak.clone();
+                    thread::spawn(move || {
+                        let mut reader = BufReader::new(stream.try_clone().unwrap());
+                        let mut request_line = String::new();
+                        reader.read_line(&mut request_line).unwrap();
+                        let path = request_line
+                            .split_whitespace()
+                            .nth(1)
+                            .unwrap_or_default()
+                            .to_owned();
+                        let mut headers = Vec::new();
+                        loop {
+                            let mut line = String::new();
+                            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                                break;
+                            }
+                            let line = line.trim_end();
+                            if line.is_empty() {
+                                break;
+                            }
+                            if let Some(idx) = line.find(':') {
+                                headers.push((
+                                    line[..idx].to_ascii_lowercase(),
+                                    line[idx + 1..].trim().to_owned(),
+                                ));
+                            }
+                        }
+                        recorded.lock().push(MockRequest { path, headers });
+                        let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now_in_flight, Ordering::SeqCst);
+
+                        let idx = served.fetch_add(1, Ordering::SeqCst);
+                        let resp = &responses[idx.min(responses.len() - 1)];
+                        if let Some(delay) = resp.delay {
+                            thread::sleep(delay);
+                        }
+                        let mut out = format!(
+                            "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                            resp.status,
+                            resp.body.len()
+                        );
+                        for (name, value) in &resp.headers {
+                            out.push_str(&format!("{}: {}\r\n", name, value));
+                        }
+                        out.push_str("\r\n");
+                        out.push_str(&resp.body);
+                        let _ = stream.write_all(out.as_bytes());
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+            });
+            MockServer {
+                url,
+                requests,
+                peak_concurrency,
+            }
+        }
+
+        /// Url to use as host of the client under test
+        pub(crate) fn url(&self) -> &str {
+            &self.url
+        }
+
+        pub(crate) fn requests(&self) -> Vec<MockRequest> {
+            self.requests.lock().clone()
+        }
+
+        /// Largest number of requests that were being answered at the same time
+        pub(crate) fn peak_concurrency(&self) -> usize {
+            self.peak_concurrency.load(Ordering::SeqCst)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_path_segment;
+
+    #[test]
+    fn encodes_path_segments() {
+        assert_eq!(encode_path_segment("Hide on bush"), "Hide%20on%20bush");
+        assert_eq!(encode_path_segment("a/b?c#d"), "a%2Fb%3Fc%23d");
+        assert_eq!(encode_path_segment("Vetro"), "Vetro");
+    }
+}